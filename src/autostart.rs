@@ -0,0 +1,309 @@
+use std::io;
+use std::path::Path;
+#[cfg(not(target_os = "windows"))]
+use std::path::PathBuf;
+
+/// Launching tailslint when the user logs in, implemented per platform.
+pub trait Autostart {
+    /// Whether tailslint is currently registered to start on login.
+    fn is_enabled(&self) -> bool;
+
+    /// Registers `exe` to be started on login.
+    fn enable(&self, exe: &Path) -> io::Result<()>;
+
+    /// Removes the login registration, if there is one.
+    fn disable(&self) -> io::Result<()>;
+}
+
+/// The autostart mechanism of the platform tailslint was built for.
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+pub fn platform() -> Box<dyn Autostart> {
+    let dir = crate::config::xdg_config_home()
+        .unwrap_or_default()
+        .join("autostart");
+    Box::new(XdgAutostart { dir })
+}
+
+/// The autostart mechanism of the platform tailslint was built for.
+#[cfg(target_os = "macos")]
+pub fn platform() -> Box<dyn Autostart> {
+    let home = std::env::var_os("HOME").map(PathBuf::from);
+    let dir = home.unwrap_or_default().join("Library/LaunchAgents");
+    Box::new(LaunchAgent { dir })
+}
+
+/// The autostart mechanism of the platform tailslint was built for.
+#[cfg(target_os = "windows")]
+pub fn platform() -> Box<dyn Autostart> {
+    Box::new(RunKey)
+}
+
+/// A desktop entry in the XDG autostart directory (`~/.config/autostart`).
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+pub struct XdgAutostart {
+    dir: PathBuf,
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+impl XdgAutostart {
+    fn path(&self) -> PathBuf {
+        self.dir.join("tailslint.desktop")
+    }
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+impl Autostart for XdgAutostart {
+    fn is_enabled(&self) -> bool {
+        self.path().exists()
+    }
+
+    fn enable(&self, exe: &Path) -> io::Result<()> {
+        std::fs::create_dir_all(&self.dir)?;
+        std::fs::write(self.path(), desktop_entry(exe))
+    }
+
+    fn disable(&self) -> io::Result<()> {
+        match std::fs::remove_file(self.path()) {
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+            result => result,
+        }
+    }
+}
+
+/// A launchd agent in `~/Library/LaunchAgents`.
+#[cfg(target_os = "macos")]
+pub struct LaunchAgent {
+    dir: PathBuf,
+}
+
+#[cfg(target_os = "macos")]
+impl LaunchAgent {
+    fn path(&self) -> PathBuf {
+        self.dir.join(format!("{LAUNCH_AGENT_LABEL}.plist"))
+    }
+}
+
+#[cfg(target_os = "macos")]
+impl Autostart for LaunchAgent {
+    fn is_enabled(&self) -> bool {
+        self.path().exists()
+    }
+
+    fn enable(&self, exe: &Path) -> io::Result<()> {
+        std::fs::create_dir_all(&self.dir)?;
+        std::fs::write(self.path(), launch_agent_plist(exe))
+    }
+
+    fn disable(&self) -> io::Result<()> {
+        match std::fs::remove_file(self.path()) {
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+            result => result,
+        }
+    }
+}
+
+/// A value under the `HKCU\...\CurrentVersion\Run` registry key, written with `reg.exe`.
+#[cfg(target_os = "windows")]
+pub struct RunKey;
+
+#[cfg(target_os = "windows")]
+impl RunKey {
+    const KEY: &'static str = r"HKCU\Software\Microsoft\Windows\CurrentVersion\Run";
+    const VALUE: &'static str = "tailslint";
+
+    fn reg(args: &[&str]) -> io::Result<()> {
+        let output = crate::process::output(std::process::Command::new("reg").args(args))?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+            return Err(io::Error::other(stderr));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(target_os = "windows")]
+impl Autostart for RunKey {
+    fn is_enabled(&self) -> bool {
+        Self::reg(&["query", Self::KEY, "/v", Self::VALUE]).is_ok()
+    }
+
+    fn enable(&self, exe: &Path) -> io::Result<()> {
+        let command = format!("\"{}\"", exe.to_string_lossy());
+        Self::reg(&[
+            "add",
+            Self::KEY,
+            "/v",
+            Self::VALUE,
+            "/t",
+            "REG_SZ",
+            "/d",
+            &command,
+            "/f",
+        ])
+    }
+
+    fn disable(&self) -> io::Result<()> {
+        if !self.is_enabled() {
+            return Ok(());
+        }
+        Self::reg(&["delete", Self::KEY, "/v", Self::VALUE, "/f"])
+    }
+}
+
+/// The desktop entry launching `exe`. `Exec` is quoted as the Desktop Entry
+/// Specification requires: `"`, `` ` ``, `$` and `\` are escaped inside the
+/// quotes, `%` is doubled, and then the string escapes apply to the whole
+/// value, so a backslash in the path ends up as four.
+#[cfg_attr(any(target_os = "macos", target_os = "windows"), allow(dead_code))]
+fn desktop_entry(exe: &Path) -> String {
+    let mut quoted = String::from("\"");
+    for c in exe.to_string_lossy().chars() {
+        match c {
+            '"' | '`' | '$' | '\\' => quoted.extend(['\\', c]),
+            '%' => quoted.push_str("%%"),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+
+    let mut exec = String::new();
+    for c in quoted.chars() {
+        match c {
+            '\\' => exec.push_str("\\\\"),
+            '\n' => exec.push_str("\\n"),
+            '\t' => exec.push_str("\\t"),
+            '\r' => exec.push_str("\\r"),
+            c => exec.push(c),
+        }
+    }
+
+    format!(
+        "[Desktop Entry]\n\
+         Type=Application\n\
+         Name=Tailslint\n\
+         Comment=Tailscale control window\n\
+         Exec={exec}\n\
+         Terminal=false\n\
+         X-GNOME-Autostart-enabled=true\n"
+    )
+}
+
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+const LAUNCH_AGENT_LABEL: &str = "dev.tailslint";
+
+/// The launchd property list launching `exe` once on login.
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn launch_agent_plist(exe: &Path) -> String {
+    let exe = exe
+        .to_string_lossy()
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;");
+
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <!DOCTYPE plist PUBLIC \"-//Apple//DTD PLIST 1.0//EN\" \"http://www.apple.com/DTDs/PropertyList-1.0.dtd\">\n\
+         <plist version=\"1.0\">\n\
+         <dict>\n\
+         \t<key>Label</key>\n\
+         \t<string>{LAUNCH_AGENT_LABEL}</string>\n\
+         \t<key>ProgramArguments</key>\n\
+         \t<array>\n\
+         \t\t<string>{exe}</string>\n\
+         \t</array>\n\
+         \t<key>RunAtLoad</key>\n\
+         \t<true/>\n\
+         </dict>\n\
+         </plist>\n"
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Paths with everything either format has to escape.
+    const EXES: [&str; 4] = [
+        "/usr/bin/tailslint",
+        "/opt/My Apps/tail&slint <beta>/tailslint",
+        "/home/a\"b`c$d\\e/100% tailslint",
+        "/tmp/odd\tname\nhere/tailslint",
+    ];
+
+    /// Reads the program back out of a desktop entry's `Exec`, undoing the
+    /// string escapes and then the quoting like a launcher does.
+    fn exec_program(entry: &str) -> String {
+        let value = entry
+            .lines()
+            .find_map(|line| line.strip_prefix("Exec="))
+            .unwrap();
+
+        let mut unescaped = String::new();
+        let mut chars = value.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => match chars.next().unwrap() {
+                    's' => unescaped.push(' '),
+                    'n' => unescaped.push('\n'),
+                    't' => unescaped.push('\t'),
+                    'r' => unescaped.push('\r'),
+                    c => unescaped.push(c),
+                },
+                c => unescaped.push(c),
+            }
+        }
+
+        let quoted = unescaped
+            .strip_prefix('"')
+            .unwrap()
+            .strip_suffix('"')
+            .unwrap();
+        let mut program = String::new();
+        let mut chars = quoted.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => program.push(chars.next().unwrap()),
+                '%' => {
+                    assert_eq!(chars.next(), Some('%'));
+                    program.push('%');
+                }
+                '"' | '`' | '$' => panic!("unescaped {c} in {value}"),
+                c => program.push(c),
+            }
+        }
+        program
+    }
+
+    #[test]
+    fn desktop_entry_round_trips() {
+        for exe in EXES {
+            let entry = desktop_entry(Path::new(exe));
+            assert_eq!(exec_program(&entry), exe);
+            // a path can't add lines to the entry
+            assert_eq!(entry.lines().count(), 7);
+        }
+
+        let entry = desktop_entry(Path::new("/home/a\\b/100%"));
+        assert!(entry.contains("Exec=\"/home/a\\\\\\\\b/100%%\"\n"));
+    }
+
+    #[test]
+    fn launch_agent_plist_round_trips() {
+        for exe in EXES {
+            let plist = launch_agent_plist(Path::new(exe));
+            let (_, rest) = plist.split_once("<array>\n\t\t<string>").unwrap();
+            let (program, _) = rest.split_once("</string>\n\t</array>").unwrap();
+            assert!(!program.contains(['<', '>']));
+            let program = program
+                .replace("&lt;", "<")
+                .replace("&gt;", ">")
+                .replace("&amp;", "&");
+            assert_eq!(program, exe);
+        }
+
+        let plist = launch_agent_plist(Path::new("/opt/a&b <c>"));
+        assert!(plist.contains("<string>/opt/a&amp;b &lt;c&gt;</string>"));
+        assert!(plist.contains("<string>dev.tailslint</string>"));
+    }
+}
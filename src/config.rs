@@ -0,0 +1,182 @@
+use serde::Deserialize;
+use std::path::PathBuf;
+use thiserror::Error;
+
+#[cfg(feature = "schedules")]
+use crate::schedule::PeerSchedule;
+#[cfg(target_os = "linux")]
+use crate::service::{Escalation, InitSystem, WatchedUnit};
+
+/// Defines the possible errors that can occur when loading the config file.
+#[derive(Error, Debug)]
+pub enum ConfigError {
+    #[error("Failed to read config file: {0}")]
+    ReadError(#[from] std::io::Error),
+
+    #[error("Failed to parse config file: {0}")]
+    ParseError(#[from] toml::de::Error),
+}
+
+/// User settings read from `$XDG_CONFIG_HOME/tailslint/config.toml`.
+/// Every key is optional and falls back to its default.
+#[derive(Deserialize, Debug)]
+#[serde(default)]
+pub struct Config {
+    /// Warn about an expiring node key this many days in advance.
+    pub key_expiry_warning_days: i64,
+
+    /// Flags passed to `tailscale up`, which then has to mention every non-default
+    /// pref. By default a bare `tailscale up` keeps the current prefs.
+    pub up_flags: Option<Vec<String>>,
+
+    /// Directory exported files are written to, the home directory by default.
+    pub export_dir: Option<PathBuf>,
+
+    /// Directory `tailscale cert` writes into, `certs` in the config directory by default.
+    pub cert_dir: Option<PathBuf>,
+
+    /// The page "Open admin console" opens, change it when using Headscale.
+    pub admin_url: String,
+
+    /// Count which features are used, see `telemetry.rs`. Off unless opted in,
+    /// and only has an effect in builds with the `telemetry` feature.
+    pub telemetry: bool,
+
+    /// Command run by "Update Tailscale", e.g. `["pkexec", "tailscale", "update", "--yes"]`.
+    pub update_command: Option<Vec<String>>,
+
+    /// User to log in as for "Mount home via sshfs", ssh's default when unset.
+    pub sshfs_user: Option<String>,
+
+    /// Send a notification whenever tailscaled reports a new health warning.
+    /// Warnings that are already there at launch are only shown in the menu.
+    pub notify_health_warnings: bool,
+
+    /// Hold back notifications about state changes, like health warnings and
+    /// peers going offline, for this many seconds after launch and after
+    /// waking from suspend, while the state settles. 0 to never hold back.
+    pub notification_settle_secs: u64,
+
+    /// Kill `tailscale` and other commands that take longer than this many
+    /// seconds, 0 to wait forever.
+    pub command_timeout_secs: u64,
+
+    /// Keep tailslint small for old machines: everything is read once a minute
+    /// instead of following tailscaled's changes, `monitor_peer`,
+    /// `peer_schedules` and `telemetry` are ignored, and the log viewer keeps
+    /// 100 lines instead of 1000.
+    pub low_memory: bool,
+
+    /// Disconnect with `tailscale down` whenever tailslint exits.
+    pub quit_disconnects: bool,
+
+    /// Stop the tailscaled unit whenever tailslint exits, on Linux with systemd.
+    pub quit_stops_daemon: bool,
+
+    /// Peer pinged every 30 seconds, its latency is shown in the Tailscale menu.
+    pub monitor_peer: Option<String>,
+
+    /// Notify when a ping to `monitor_peer` takes longer than this many milliseconds.
+    pub monitor_threshold_ms: u64,
+
+    /// Peers to alert about when they are online or offline unexpectedly.
+    #[cfg(feature = "schedules")]
+    pub peer_schedules: Vec<PeerSchedule>,
+
+    /// systemd units to start and stop from the Services menu.
+    #[cfg(target_os = "linux")]
+    pub services: Vec<WatchedUnit>,
+
+    /// The init system controlling services, detected from PID 1 when unset.
+    #[cfg(target_os = "linux")]
+    pub init_system: Option<InitSystem>,
+
+    /// How OpenRC, runit and dinit commands get root, detected when unset.
+    /// When set, it also applies to systemd, `run0` from systemd 256.
+    #[cfg(target_os = "linux")]
+    pub escalation: Option<Escalation>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            key_expiry_warning_days: 7,
+            up_flags: None,
+            export_dir: None,
+            cert_dir: None,
+            admin_url: "https://login.tailscale.com/admin/machines".into(),
+            telemetry: false,
+            update_command: None,
+            sshfs_user: None,
+            notify_health_warnings: false,
+            notification_settle_secs: 15,
+            command_timeout_secs: 30,
+            low_memory: false,
+            quit_disconnects: false,
+            quit_stops_daemon: false,
+            monitor_peer: None,
+            monitor_threshold_ms: 250,
+            #[cfg(feature = "schedules")]
+            peer_schedules: Vec::new(),
+            #[cfg(target_os = "linux")]
+            services: Vec::new(),
+            #[cfg(target_os = "linux")]
+            init_system: None,
+            #[cfg(target_os = "linux")]
+            escalation: None,
+        }
+    }
+}
+
+impl Config {
+    /// Loads the config file, returning the defaults if it does not exist.
+    pub fn load() -> Result<Config, ConfigError> {
+        let Some(path) = Self::path() else {
+            return Ok(Config::default());
+        };
+
+        if !path.exists() {
+            return Ok(Config::default());
+        }
+
+        let contents = std::fs::read_to_string(path)?;
+        Ok(toml::from_str(&contents)?)
+    }
+
+    /// The directory exported files are written to.
+    #[cfg(feature = "inventory")]
+    pub fn export_dir(&self) -> PathBuf {
+        match &self.export_dir {
+            Some(dir) => dir.clone(),
+            None => std::env::var_os("HOME")
+                .map(PathBuf::from)
+                .unwrap_or_default(),
+        }
+    }
+
+    /// The directory fetched TLS certificates are written to.
+    pub fn cert_dir(&self) -> PathBuf {
+        match &self.cert_dir {
+            Some(dir) => dir.clone(),
+            None => config_dir().unwrap_or_default().join("certs"),
+        }
+    }
+
+    /// The location of the config file, if a config directory can be determined.
+    pub fn path() -> Option<PathBuf> {
+        config_dir().map(|dir| dir.join("config.toml"))
+    }
+}
+
+/// The directory holding tailslint's own files, `$XDG_CONFIG_HOME/tailslint`.
+pub fn config_dir() -> Option<PathBuf> {
+    xdg_config_home().map(|dir| dir.join("tailslint"))
+}
+
+/// The user's base config directory, `$XDG_CONFIG_HOME` or `~/.config`.
+pub fn xdg_config_home() -> Option<PathBuf> {
+    match std::env::var_os("XDG_CONFIG_HOME") {
+        Some(dir) if !dir.is_empty() => Some(PathBuf::from(dir)),
+        _ => Some(PathBuf::from(std::env::var_os("HOME")?).join(".config")),
+    }
+}
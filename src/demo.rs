@@ -0,0 +1,347 @@
+//! A simulated tailnet answering in place of the `tailscale` CLI, enabled with
+//! `--demo` or `--demo=<seed>`. Used for screenshots and UI work without a real
+//! tailnet. The same seed always produces the same peers, and peers go offline
+//! and come back on a fixed schedule so state changes can be observed.
+
+use serde_json::{Value, json};
+use std::io;
+use std::sync::Mutex;
+use std::time::Instant;
+
+use crate::process::{self, CommandOutput, CommandRunner, SystemRunner};
+
+const VERSION: &str = "1.70.0";
+const LATEST_VERSION: &str = "1.72.1";
+const TAILNET: &str = "example.ts.net";
+
+/// How long a peer stays in one state before the schedule may flip it.
+const PHASE_SECS: u64 = 20;
+
+const HOSTNAMES: [&str; 12] = [
+    "atlas", "birch", "cobalt", "dune", "ember", "fjord", "granite", "harbor", "iris", "juniper",
+    "kestrel", "lumen",
+];
+const OSES: [&str; 5] = ["linux", "windows", "macOS", "iOS", "android"];
+const ACCOUNTS: [&str; 2] = ["alice@example.com", "alice@work.example"];
+
+static DEMO: Mutex<Option<Demo>> = Mutex::new(None);
+
+struct Peer {
+    hostname: String,
+    ipv4: String,
+    ipv6: String,
+    os: &'static str,
+    exit_node: bool,
+    phase: u64,
+}
+
+struct Demo {
+    started: Instant,
+    running: bool,
+    hostname: String,
+    active_profile: usize,
+    peers: Vec<Peer>,
+}
+
+/// A small deterministic PRNG (SplitMix64), good enough for made-up hostnames.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    fn below(&mut self, n: u64) -> u64 {
+        self.next() % n
+    }
+}
+
+/// Returns the seed if `--demo` or `--demo=<seed>` was passed.
+pub fn seed_from_args(mut args: impl Iterator<Item = String>) -> Option<u64> {
+    args.find_map(|arg| match arg.as_str() {
+        "--demo" => Some(1),
+        arg => arg.strip_prefix("--demo=")?.parse().ok(),
+    })
+}
+
+/// Switches every `tailscale` invocation over to a simulated tailnet.
+pub fn start(seed: u64) {
+    let mut rng = Rng(seed);
+    let count = 5 + rng.below(6) as usize;
+
+    let mut hostnames: Vec<&str> = HOSTNAMES.to_vec();
+    let mut peers = Vec::with_capacity(count);
+    for i in 0..count {
+        let hostname = hostnames.remove(rng.below(hostnames.len() as u64) as usize);
+        let (a, b) = (64 + rng.below(64), 1 + rng.below(254));
+        peers.push(Peer {
+            hostname: hostname.to_string(),
+            ipv4: format!("100.{a}.{b}.{}", 1 + i),
+            ipv6: format!("fd7a:115c:a1e0::{a:x}{b:02x}:{:x}", 1 + i),
+            os: OSES[rng.below(OSES.len() as u64) as usize],
+            exit_node: rng.below(3) == 0,
+            phase: rng.below(4),
+        });
+    }
+
+    *DEMO.lock().unwrap() = Some(Demo {
+        started: Instant::now(),
+        running: true,
+        hostname: "demo-laptop".into(),
+        active_profile: 0,
+        peers,
+    });
+    process::set_runner(DemoRunner);
+}
+
+/// Answers `tailscale` invocations from the simulated tailnet, anything else
+/// runs as usual.
+struct DemoRunner;
+
+impl CommandRunner for DemoRunner {
+    fn run(&self, program: &str, args: &[&str]) -> io::Result<CommandOutput> {
+        match program {
+            "tailscale" => Ok(execute(args).expect("demo mode is active")),
+            _ => SystemRunner.run(program, args),
+        }
+    }
+}
+
+/// Whether `start()` switched over to the simulated tailnet.
+pub fn is_active() -> bool {
+    DEMO.lock().unwrap().is_some()
+}
+
+/// Answers a `tailscale` invocation if demo mode is active.
+fn execute(args: &[&str]) -> Option<CommandOutput> {
+    let mut demo = DEMO.lock().unwrap();
+    let demo = demo.as_mut()?;
+
+    let stdout = match args {
+        ["up", ..] => {
+            demo.running = true;
+            String::new()
+        }
+        ["down"] => {
+            demo.running = false;
+            String::new()
+        }
+        ["status"] if !demo.running => "Tailscale is stopped.\n".into(),
+        ["status"] => demo.status_text(),
+        ["status", "--json"] => demo.status_json().to_string(),
+        ["ip"] if !demo.running => return Some(failure("Tailscale is stopped.")),
+        ["ip"] => "100.100.1.1\nfd7a:115c:a1e0::1\n".into(),
+        ["switch", "--list"] => demo.profiles(),
+        ["switch", profile] => {
+            let index = ACCOUNTS.iter().position(|account| account == profile);
+            let id = profile
+                .parse::<usize>()
+                .ok()
+                .and_then(|id| id.checked_sub(1));
+            match index.or(id).filter(|i| *i < ACCOUNTS.len()) {
+                Some(i) => demo.active_profile = i,
+                None => return Some(failure("no profile found")),
+            }
+            String::new()
+        }
+        ["set", flag] if flag.starts_with("--hostname=") => {
+            demo.hostname = flag["--hostname=".len()..].to_string();
+            String::new()
+        }
+        ["version"] => format!("{VERSION}\n  tailscale commit: demo\n"),
+        ["serve", "status", "--json"] => "{}".into(),
+        ["drive", "list"] => "name  path  as\n----  ----  --\n".into(),
+        ["netcheck"] => NETCHECK.into(),
+        ["metrics", "print"] => METRICS.into(),
+        ["dns", "status"] => DNS_STATUS.replace("{TAILNET}", TAILNET),
+        ["ping", .., target] => {
+            let peer = demo.online_peers().find(|peer| peer.hostname == *target);
+            match peer {
+                Some(peer) => format!(
+                    "pong from {} ({}) via DERP(fra) in {}ms\n",
+                    peer.hostname,
+                    peer.ipv4,
+                    20 + 7 * peer.phase
+                ),
+                None => return Some(failure("timeout waiting for ping reply")),
+            }
+        }
+        ["bugreport"] => "BUG-0a1b2c3d4e5f60718293a4b5c6d7e8f9-20240601120000Z-demo\n".into(),
+        ["exit-node", "suggest"] => match demo.online_peers().find(|peer| peer.exit_node) {
+            Some(peer) => format!("Suggested exit node: {}.{TAILNET}.\n", peer.hostname),
+            None => "No exit node suggestion is available.\n".into(),
+        },
+        _ => String::new(),
+    };
+
+    Some(CommandOutput {
+        success: true,
+        stdout,
+        stderr: String::new(),
+    })
+}
+
+fn failure(stderr: &str) -> CommandOutput {
+    CommandOutput {
+        success: false,
+        stdout: String::new(),
+        stderr: stderr.to_string(),
+    }
+}
+
+impl Demo {
+    fn is_online(&self, peer: &Peer) -> bool {
+        let phase = self.started.elapsed().as_secs() / PHASE_SECS;
+        !(phase + peer.phase).is_multiple_of(4)
+    }
+
+    fn online_peers(&self) -> impl Iterator<Item = &Peer> {
+        self.peers.iter().filter(|peer| self.is_online(peer))
+    }
+
+    fn account(&self) -> &'static str {
+        ACCOUNTS[self.active_profile]
+    }
+
+    fn status_text(&self) -> String {
+        let user = self.account().split('@').next().unwrap_or_default();
+        let mut status = format!("100.100.1.1     {:<12} {user}@  linux   -\n", self.hostname);
+
+        for (i, peer) in self.peers.iter().enumerate() {
+            let details = match self.is_online(peer) {
+                true => format!(
+                    "active; direct 203.0.113.{}:41641, tx {} rx {}",
+                    10 + i,
+                    (i as u64 + 1) * 48_213,
+                    (i as u64 + 1) * 1_337_421
+                ),
+                false => "offline".into(),
+            };
+            status.push_str(&format!(
+                "{:<15} {:<12} {user}@  {:<7} {details}\n",
+                peer.ipv4, peer.hostname, peer.os
+            ));
+        }
+        status
+    }
+
+    fn status_json(&self) -> Value {
+        let key_expiry = chrono::Utc::now() + chrono::TimeDelta::days(30);
+
+        let peers: serde_json::Map<String, Value> = self
+            .peers
+            .iter()
+            .enumerate()
+            .map(|(i, peer)| {
+                let node = json!({
+                    "ID": format!("demo-node-{i}"),
+                    "HostName": peer.hostname,
+                    "DNSName": format!("{}.{TAILNET}.", peer.hostname),
+                    "OS": peer.os,
+                    "UserID": 1,
+                    "TailscaleIPs": [peer.ipv4, peer.ipv6],
+                    "Online": self.is_online(peer),
+                    "Active": self.is_online(peer),
+                    "CurAddr": format!("203.0.113.{}:41641", 10 + i),
+                    "TxBytes": (i as u64 + 1) * 48_213,
+                    "RxBytes": (i as u64 + 1) * 1_337_421,
+                    "ExitNodeOption": peer.exit_node,
+                    "LastSeen": "0001-01-01T00:00:00Z",
+                });
+                (format!("nodekey:demo{i}"), node)
+            })
+            .collect();
+
+        json!({
+            "Version": VERSION,
+            "BackendState": if self.running { "Running" } else { "Stopped" },
+            "Self": {
+                "ID": "demo-self",
+                "HostName": self.hostname,
+                "DNSName": format!("{}.{TAILNET}.", self.hostname),
+                "OS": "linux",
+                "UserID": 1,
+                "TailscaleIPs": ["100.100.1.1", "fd7a:115c:a1e0::1"],
+                "Online": self.running,
+                "KeyExpiry": key_expiry.to_rfc3339(),
+            },
+            "CurrentTailnet": { "Name": TAILNET, "MagicDNSSuffix": TAILNET, "MagicDNSEnabled": true },
+            "User": { "1": { "ID": 1, "LoginName": self.account(), "DisplayName": "Alice" } },
+            "Peer": peers,
+            "ClientVersion": { "RunningLatest": false, "LatestVersion": LATEST_VERSION },
+        })
+    }
+
+    fn profiles(&self) -> String {
+        let mut list = String::from("ID    Tailnet             Account\n");
+        for (i, account) in ACCOUNTS.iter().enumerate() {
+            let active = if i == self.active_profile { "*" } else { "" };
+            let tailnet = account.split('@').nth(1).unwrap_or_default();
+            list.push_str(&format!("{:<5} {tailnet:<19} {account}{active}\n", i + 1));
+        }
+        list
+    }
+}
+
+const METRICS: &str = "\
+# TYPE tailscaled_advertised_routes gauge
+tailscaled_advertised_routes 0
+# TYPE tailscaled_inbound_bytes_total counter
+tailscaled_inbound_bytes_total{path=\"derp\"} 48213
+tailscaled_inbound_bytes_total{path=\"direct_ipv4\"} 10485760
+# TYPE tailscaled_inbound_dropped_packets_total counter
+tailscaled_inbound_dropped_packets_total{reason=\"acl\"} 3
+# TYPE tailscaled_outbound_bytes_total counter
+tailscaled_outbound_bytes_total{path=\"derp\"} 51002
+tailscaled_outbound_bytes_total{path=\"direct_ipv4\"} 2097152
+# TYPE tailscaled_outbound_dropped_packets_total counter
+tailscaled_outbound_dropped_packets_total{reason=\"error\"} 0
+";
+
+const DNS_STATUS: &str = "
+=== 'Use Tailscale DNS' status ===
+
+Tailscale DNS: enabled.
+
+=== MagicDNS configuration ===
+
+MagicDNS: enabled tailnet-wide (suffix = {TAILNET}).
+
+=== Resolvers configured for this device ===
+
+Tailscale will use the following resolvers to resolve DNS queries:
+  - 1.1.1.1
+  - 9.9.9.9
+
+=== Split DNS Routes ===
+
+Tailscale will route queries for these suffixes to the corresponding resolvers:
+  - corp.example -> 10.0.0.53
+
+=== Search Domains ===
+
+Tailscale will append the following search domains to single-label DNS queries:
+  - {TAILNET}
+";
+
+const NETCHECK: &str = "
+Report:
+\t* Time: 2024-06-01T12:00:00Z
+\t* UDP: true
+\t* IPv4: yes, 198.51.100.7:41641
+\t* IPv6: no, but OS has support
+\t* MappingVariesByDestIP: false
+\t* PortMapping: UPnP
+\t* CaptivePortal: false
+\t* Nearest DERP: Frankfurt
+\t* DERP latency:
+\t\t- fra: 11.2ms  (Frankfurt)
+\t\t- ams: 17.8ms  (Amsterdam)
+\t\t- lhr: 24.5ms  (London)
+\t\t- nyc: 92.1ms  (New York City)
+\t\t- sfo: 151.3ms (San Francisco)
+";
@@ -0,0 +1,9 @@
+use std::process::Command;
+
+use crate::process;
+
+/// Opens a path or URL with the desktop's default handler via `xdg-open`.
+/// The handler is not waited for, it usually keeps running on its own.
+pub fn open(target: &str) -> std::io::Result<()> {
+    process::spawn_detached(Command::new("xdg-open").arg(target))
+}
@@ -0,0 +1,82 @@
+//! A named pipe at `$XDG_RUNTIME_DIR/tailslint.cmd` for triggering actions
+//! from shell scripts and window manager key bindings, e.g.
+//! `echo toggle > $XDG_RUNTIME_DIR/tailslint.cmd`. Every line is one command,
+//! run as if the matching menu entry was clicked.
+
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::os::unix::fs::FileTypeExt;
+use std::path::PathBuf;
+use std::process::Command;
+use std::thread;
+
+use slint::ComponentHandle;
+
+use crate::{MainWindow, process, update_tailscale_state};
+
+/// The pipe's location, if there is a runtime directory.
+fn path() -> Option<PathBuf> {
+    let dir = std::env::var_os("XDG_RUNTIME_DIR").filter(|dir| !dir.is_empty())?;
+    Some(PathBuf::from(dir).join("tailslint.cmd"))
+}
+
+/// Creates the pipe and starts reading commands from it in the background.
+pub fn start(main_window_weak: slint::Weak<MainWindow>) {
+    let Some(path) = path() else {
+        return;
+    };
+
+    let is_fifo = std::fs::metadata(&path).is_ok_and(|meta| meta.file_type().is_fifo());
+    if !is_fifo {
+        let _ = std::fs::remove_file(&path);
+        match process::output(Command::new("mkfifo").arg("-m").arg("600").arg(&path)) {
+            Ok(output) if output.status.success() => {}
+            Ok(output) => {
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                eprintln!("Failed to create {}: {}", path.display(), stderr.trim());
+                return;
+            }
+            Err(e) => {
+                eprintln!("Failed to create {}: {e}", path.display());
+                return;
+            }
+        }
+    }
+
+    thread::spawn(move || {
+        // opening blocks until a writer shows up, and every writer closing the
+        // pipe ends the stream, so it is reopened for the next one
+        while let Ok(file) = File::open(&path) {
+            for line in BufReader::new(file).lines().map_while(Result::ok) {
+                let command = line.trim().to_string();
+                let _ = main_window_weak.upgrade_in_event_loop(move |main_window| {
+                    run(&main_window, &command);
+                });
+            }
+        }
+    });
+}
+
+/// Removes the pipe again when tailslint quits.
+pub fn remove() {
+    if let Some(path) = path() {
+        let _ = std::fs::remove_file(path);
+    }
+}
+
+fn run(main_window: &MainWindow, command: &str) {
+    let words: Vec<&str> = command.split_whitespace().collect();
+    match words.as_slice() {
+        [] => {}
+        ["toggle"] | ["toggle", "tailscale"] => main_window.invoke_toggle(),
+        ["refresh"] => update_tailscale_state(main_window),
+        ["netcheck"] => main_window.invoke_run_netcheck(),
+        ["exit-node"] => main_window.invoke_use_suggested_exit_node(),
+        ["bugreport"] => main_window.invoke_create_bugreport(),
+        ["show"] => {
+            let _ = main_window.show();
+        }
+        ["quit"] => main_window.invoke_quit(),
+        _ => eprintln!("Unknown command on the command pipe: {command}"),
+    }
+}
@@ -0,0 +1,97 @@
+//! Human readable formatting shared by the window, notifications and dialogs.
+//! This is not localization: only the number format follows the locale, by
+//! taking its decimal separator on Linux. Words like "days" and "ago" and the
+//! unit names stay English like the rest of the window.
+
+use chrono::{DateTime, TimeDelta, Utc};
+
+/// Formats a length of time in its largest sensible unit, e.g. "12 days" or "3 hours".
+pub fn duration(duration: TimeDelta) -> String {
+    let duration = duration.abs();
+
+    match duration {
+        d if d.num_days() > 0 => plural(d.num_days(), "day"),
+        d if d.num_hours() > 0 => plural(d.num_hours(), "hour"),
+        d if d.num_minutes() > 0 => plural(d.num_minutes(), "minute"),
+        d => plural(d.num_seconds(), "second"),
+    }
+}
+
+/// Formats a length of time in its two largest units, e.g. "2h 13m" or "45s".
+pub fn uptime(duration: TimeDelta) -> String {
+    let duration = duration.abs();
+    let (days, hours) = (duration.num_days(), duration.num_hours() % 24);
+    let (minutes, seconds) = (duration.num_minutes() % 60, duration.num_seconds() % 60);
+
+    match (days, hours, minutes) {
+        (0, 0, 0) => format!("{seconds}s"),
+        (0, 0, _) => format!("{minutes}m {seconds}s"),
+        (0, _, _) => format!("{hours}h {minutes}m"),
+        _ => format!("{days}d {hours}h"),
+    }
+}
+
+/// Formats a point in time relative to now, e.g. "in 12 days" or "3 hours ago".
+pub fn relative(time: DateTime<Utc>) -> String {
+    let delta = time - Utc::now();
+
+    if delta >= TimeDelta::zero() {
+        format!("in {}", duration(delta))
+    } else {
+        format!("{} ago", duration(delta))
+    }
+}
+
+/// Formats a byte count with binary units, e.g. "1.5 MiB", or "1,5 MiB" with a
+/// comma as the locale's decimal separator.
+pub fn bytes(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["KiB", "MiB", "GiB", "TiB", "PiB"];
+
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+
+    let value = format!("{value:.1}").replace('.', &decimal_separator());
+    format!("{value} {}", UNITS[unit])
+}
+
+fn plural(count: i64, unit: &str) -> String {
+    match count {
+        1 => format!("1 {unit}"),
+        _ => format!("{count} {unit}s"),
+    }
+}
+
+/// The decimal separator of the numeric locale picked by `LC_ALL`, `LC_NUMERIC`
+/// or `LANG`, as the C library defines it. A point if that locale isn't
+/// installed.
+#[cfg(target_os = "linux")]
+fn decimal_separator() -> String {
+    use std::ffi::CStr;
+
+    // SAFETY: the locale is only read here and freed after, the separator is
+    // copied out of it before that
+    unsafe {
+        let locale = libc::newlocale(libc::LC_NUMERIC_MASK, c"".as_ptr(), std::ptr::null_mut());
+        if locale.is_null() {
+            return ".".to_string();
+        }
+        let separator = CStr::from_ptr(libc::nl_langinfo_l(libc::RADIXCHAR, locale))
+            .to_string_lossy()
+            .into_owned();
+        libc::freelocale(locale);
+        separator
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn decimal_separator() -> String {
+    ".".to_string()
+}
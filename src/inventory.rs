@@ -0,0 +1,90 @@
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+use crate::tailscale::{Peer, Tailscale, TailscaleError};
+
+/// Defines the possible errors that can occur when exporting the peer inventory.
+#[derive(Error, Debug)]
+pub enum InventoryError {
+    #[error("Failed to query peers: {0}")]
+    Tailscale(#[from] TailscaleError),
+
+    #[error("Failed to serialize inventory: {0}")]
+    SerializeError(#[from] serde_json::Error),
+
+    #[error("Failed to write inventory: {0}")]
+    WriteError(#[from] std::io::Error),
+}
+
+/// The file formats the inventory can be exported as.
+#[derive(Clone, Copy, Debug)]
+pub enum Format {
+    Csv,
+    Json,
+}
+
+impl Format {
+    pub fn from_name(name: &str) -> Option<Format> {
+        match name {
+            "csv" => Some(Format::Csv),
+            "json" => Some(Format::Json),
+            _ => None,
+        }
+    }
+
+    fn extension(self) -> &'static str {
+        match self {
+            Format::Csv => "csv",
+            Format::Json => "json",
+        }
+    }
+}
+
+/// Writes the full peer list to `tailnet-inventory.<ext>` in `dir`
+/// and returns the path of the written file.
+pub fn export(format: Format, dir: &Path) -> Result<PathBuf, InventoryError> {
+    let peers = Tailscale::peers()?;
+
+    let contents = match format {
+        Format::Csv => to_csv(&peers),
+        Format::Json => serde_json::to_string_pretty(&peers)?,
+    };
+
+    std::fs::create_dir_all(dir)?;
+    let path = dir.join(format!("tailnet-inventory.{}", format.extension()));
+    std::fs::write(&path, contents)?;
+    Ok(path)
+}
+
+fn to_csv(peers: &[Peer]) -> String {
+    let mut csv = String::from("hostname,dns_name,ips,owner,os,tags,online,last_seen\n");
+
+    for peer in peers {
+        let last_seen = peer.last_seen.map(|t| t.to_rfc3339()).unwrap_or_default();
+        let fields = [
+            peer.hostname.as_str(),
+            peer.dns_name.as_str(),
+            &peer.ips.join(" "),
+            peer.owner.as_str(),
+            peer.os.as_str(),
+            &peer.tags.join(" "),
+            if peer.online { "true" } else { "false" },
+            &last_seen,
+        ];
+
+        let row: Vec<String> = fields.iter().map(|field| csv_field(field)).collect();
+        csv.push_str(&row.join(","));
+        csv.push('\n');
+    }
+
+    csv
+}
+
+/// Quotes a CSV field if it contains a separator, quote or line break.
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
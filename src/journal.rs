@@ -0,0 +1,130 @@
+//! Following a unit's journal for the log viewer, by reading the JSON output of
+//! `journalctl --follow` line by line on a background thread.
+
+use chrono::{DateTime, Local};
+use std::io::{self, BufRead, BufReader, Read};
+use std::process::Command;
+use std::thread;
+
+use crate::process;
+use crate::service::Scope;
+
+/// A journal message.
+pub struct Entry {
+    pub time: Option<DateTime<Local>>,
+    /// syslog priority, 0 (emergency) to 7 (debug).
+    pub priority: u8,
+    pub message: String,
+}
+
+/// A running `journalctl --follow`, stopped when dropped or when tailslint
+/// quits. Either closes the pipe, which ends the reading thread.
+pub struct Follower {
+    _child: process::Streaming,
+}
+
+/// Calls `on_entry` for the last `lines` messages of `unit` and every message
+/// logged after, until the returned follower is dropped.
+pub fn follow(
+    unit: &str,
+    scope: Scope,
+    lines: usize,
+    mut on_entry: impl FnMut(Entry) + Send + 'static,
+) -> io::Result<Follower> {
+    let unit_flag = match scope {
+        Scope::System => "--unit",
+        Scope::User => "--user-unit",
+    };
+    let (child, stdout) = process::spawn_streaming(
+        Command::new("journalctl")
+            .args([unit_flag, unit, "--follow", "--no-pager", "--output=json"])
+            .arg(format!("--lines={lines}"))
+            .env("LC_ALL", "C"),
+    )?;
+
+    thread::spawn(move || {
+        let mut reader = BufReader::new(stdout);
+        let mut line = Vec::new();
+        loop {
+            line.clear();
+            match (&mut reader)
+                .take(process::MAX_OUTPUT)
+                .read_until(b'\n', &mut line)
+            {
+                Ok(0) | Err(_) => return,
+                Ok(_) => {
+                    if let Some(entry) = parse(&line) {
+                        on_entry(entry);
+                    }
+                }
+            }
+        }
+    });
+
+    Ok(Follower { _child: child })
+}
+
+/// Reads an entry from one line of `journalctl --output=json`.
+fn parse(line: &[u8]) -> Option<Entry> {
+    let fields: serde_json::Value = serde_json::from_slice(line).ok()?;
+
+    // messages that aren't valid UTF-8 are arrays of bytes
+    let message = match &fields["MESSAGE"] {
+        serde_json::Value::String(message) => message.as_bytes().to_vec(),
+        serde_json::Value::Array(bytes) => bytes
+            .iter()
+            .filter_map(|byte| byte.as_u64().map(|byte| byte as u8))
+            .collect(),
+        _ => return None,
+    };
+    let message = String::from_utf8_lossy(&process::sanitize(&message))
+        .trim_end()
+        .to_string();
+
+    let time = fields["__REALTIME_TIMESTAMP"]
+        .as_str()
+        .and_then(|micros| micros.parse().ok())
+        .and_then(DateTime::from_timestamp_micros)
+        .map(|time| time.with_timezone(&Local));
+
+    Some(Entry {
+        time,
+        // informational, like journalctl itself, if a message has none
+        priority: fields["PRIORITY"]
+            .as_str()
+            .and_then(|priority| priority.parse().ok())
+            .unwrap_or(6),
+        message,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LINE: &[u8] = br#"{"__REALTIME_TIMESTAMP":"1760428800123456","PRIORITY":"3","_SYSTEMD_UNIT":"tailscaled.service","MESSAGE":"magicsock: \u001b[31mdisco\u001b[0m timeout"}"#;
+
+    #[test]
+    fn parse_entry() {
+        let entry = parse(LINE).unwrap();
+        assert_eq!(entry.priority, 3);
+        assert_eq!(entry.message, "magicsock: disco timeout");
+        assert_eq!(
+            entry.time.map(|time| time.timestamp_micros()),
+            Some(1760428800123456)
+        );
+
+        // not valid UTF-8, so journalctl prints the bytes
+        let entry = parse(br#"{"MESSAGE":[104,105,255,10]}"#).unwrap();
+        assert_eq!(entry.message, "hi\u{fffd}");
+        assert_eq!(entry.priority, 6);
+        assert!(entry.time.is_none());
+    }
+
+    #[test]
+    fn parse_arbitrary_lines() {
+        for input in process::fuzz::inputs(&[LINE], 5000) {
+            let _ = parse(&input);
+        }
+    }
+}
@@ -0,0 +1,86 @@
+//! A minimal client for tailscaled's LocalAPI, the HTTP interface the `tailscale`
+//! CLI itself talks to over a unix socket. Only plain GET requests are needed,
+//! so HTTP/1.0 is spoken to get an unchunked body followed by a closed connection.
+
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::os::unix::net::UnixStream;
+use std::path::Path;
+use std::time::Duration;
+
+use crate::process;
+
+/// Where tailscaled listens on Linux and the BSDs.
+pub const SOCKET: &str = "/var/run/tailscale/tailscaled.sock";
+
+/// Answers the LocalAPI has to give before it is considered unreachable.
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Requests `path`, e.g. `/localapi/v0/status`, and returns the response body,
+/// or the status line and body as the error message if tailscaled refused.
+pub fn get(path: &str) -> io::Result<String> {
+    let stream = request(path)?;
+    stream.set_read_timeout(Some(TIMEOUT))?;
+
+    let mut response = Vec::new();
+    stream
+        .take(process::MAX_OUTPUT)
+        .read_to_end(&mut response)?;
+    let response = String::from_utf8_lossy(&process::sanitize(&response)).to_string();
+
+    // sanitizing dropped the carriage returns of the CRLF line endings
+    let (head, body) = response
+        .split_once("\n\n")
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "malformed LocalAPI response"))?;
+    let status_line = head.lines().next().unwrap_or_default();
+
+    match status_line.split_whitespace().nth(1) {
+        Some("200") => Ok(body.to_string()),
+        _ => Err(io::Error::other(format!("{status_line}: {}", body.trim()))),
+    }
+}
+
+/// Requests a streaming endpoint, e.g. `/localapi/v0/watch-ipn-bus`, calling
+/// `on_line` for every line of the body until tailscaled closes the stream.
+pub fn watch(path: &str, mut on_line: impl FnMut(&str)) -> io::Result<()> {
+    let mut reader = BufReader::new(request(path)?);
+
+    let mut status_line = String::new();
+    reader.read_line(&mut status_line)?;
+    if status_line.split_whitespace().nth(1) != Some("200") {
+        return Err(io::Error::other(status_line.trim().to_string()));
+    }
+
+    // skip the headers
+    let mut line = Vec::new();
+    loop {
+        line.clear();
+        if reader.read_until(b'\n', &mut line)? == 0 || line.trim_ascii().is_empty() {
+            break;
+        }
+    }
+
+    loop {
+        line.clear();
+        if (&mut reader)
+            .take(process::MAX_OUTPUT)
+            .read_until(b'\n', &mut line)?
+            == 0
+        {
+            return Ok(());
+        }
+        on_line(&String::from_utf8_lossy(&process::sanitize(&line)));
+    }
+}
+
+/// Connects to tailscaled and sends a GET request for `path`.
+fn request(path: &str) -> io::Result<UnixStream> {
+    let mut stream = UnixStream::connect(Path::new(SOCKET))?;
+    stream.set_write_timeout(Some(TIMEOUT))?;
+
+    // the LocalAPI only answers requests addressed to this host name
+    write!(
+        stream,
+        "GET {path} HTTP/1.0\r\nHost: local-tailscaled.sock\r\n\r\n"
+    )?;
+    Ok(stream)
+}
@@ -1,18 +1,100 @@
-use cli_clipboard;
+use chrono::{DateTime, Utc};
+use slint::{Model, SharedString};
+use std::cell::Cell;
+#[cfg(target_os = "linux")]
+use std::cell::RefCell;
+#[cfg(target_os = "linux")]
+use std::collections::VecDeque;
+use std::process::Command;
 use std::rc::Rc;
+use std::sync::atomic::{AtomicBool, AtomicI64, Ordering};
+#[cfg(target_os = "linux")]
+use std::sync::{Arc, Mutex};
 use std::thread;
-use std::time::Duration;
 
-use crate::tailscale::Tailscale;
+use crate::config::Config;
+use crate::tailscale::{Tailscale, TailscaleError, TailscaleState, TailscaleStatus};
+mod autostart;
+mod config;
+mod demo;
+mod desktop;
+#[cfg(all(unix, feature = "scripting"))]
+mod fifo;
+mod format;
+#[cfg(feature = "inventory")]
+mod inventory;
+#[cfg(target_os = "linux")]
+mod journal;
+#[cfg(all(unix, feature = "localapi"))]
+mod localapi;
+mod monitor;
+mod notification;
+#[cfg(target_os = "linux")]
+mod polkit_rule;
+mod process;
+mod retry;
+#[cfg(feature = "schedules")]
+mod schedule;
+#[cfg(target_os = "linux")]
+mod sd_notify;
+#[cfg(target_os = "linux")]
+mod service;
+#[cfg(target_os = "linux")]
+mod sshfs;
+#[cfg(target_os = "linux")]
+mod sudoers;
 mod tailscale;
+mod telemetry;
+mod tour;
+#[cfg(target_os = "linux")]
+mod user_service;
+#[cfg(all(unix, feature = "localapi"))]
+mod watch;
+
+/// How long to wait for tailscaled to settle after starting or stopping it.
+#[cfg(target_os = "linux")]
+const SETTLE_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// The most journal messages kept for the log viewer, and with `low_memory`.
+#[cfg(target_os = "linux")]
+const MAX_LOG_LINES: usize = 1000;
+#[cfg(target_os = "linux")]
+const LOW_MEMORY_LOG_LINES: usize = 100;
+
+/// How often everything is read again with `low_memory`, instead of following
+/// tailscaled's changes and refreshing the Services menu every 10 seconds.
+const LOW_MEMORY_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60);
+
+/// Whether new health warnings are also sent as notifications, from the config.
+static NOTIFY_HEALTH_WARNINGS: AtomicBool = AtomicBool::new(false);
+
+/// How many days in advance an expiring node key is warned about, from the config.
+static KEY_EXPIRY_WARNING_DAYS: AtomicI64 = AtomicI64::new(7);
+
+/// The expiry of the node key last warned about, so every key is warned about
+/// once, and again after re-authenticating gets one with a new expiry.
+static WARNED_KEY_EXPIRY: Mutex<Option<DateTime<Utc>>> = Mutex::new(None);
+
+/// How often the state is read again regardless of changes, so a key expiring
+/// meanwhile is warned about without waiting for tailscaled to change.
+const KEY_EXPIRY_INTERVAL: std::time::Duration = std::time::Duration::from_secs(24 * 60 * 60);
+
+/// Whether the Services menu is paused from the Settings menu, so the units are
+/// neither read nor changed until it is resumed.
+#[cfg(target_os = "linux")]
+static SERVICES_PAUSED: AtomicBool = AtomicBool::new(false);
+
+/// Whether the health warnings were read while connected yet. The ones already
+/// there at that point are shown but not notified about.
+static HEALTH_WARNINGS_SEEDED: AtomicBool = AtomicBool::new(false);
 
 slint::slint! {
-    import { ScrollView } from "std-widgets.slint";
+    import { Button, LineEdit, ScrollView } from "std-widgets.slint";
 
-    export component ToggleSwitch inherits Rectangle {
+    component ToggleSwitch inherits Rectangle {
         callback toggled;
         in-out property <string> text;
-        in-out property <bool> checked;
+        in property <bool> checked;
         in-out property<bool> enabled <=> touch-area.enabled;
         height: 20px;
         horizontal-stretch: 0;
@@ -52,21 +134,110 @@ slint::slint! {
             width: root.width;
             height: root.height;
             clicked => {
+                // `checked` follows the caller's state, so keyboard toggles stay in sync
                 if (root.enabled) {
-                    root.checked = !root.checked;
                     root.toggled();
                 }
             }
         }
     }
 
-    export struct MachineData  {
+    export struct ServiceData {
+        unit: string,
+        active: bool,
+        // e.g. "active (running), PID 812, 45.3 MiB"
+        state: string,
+        // started at boot, only changeable for units with an [Install] section
+        enabled: bool,
+        can_enable: bool,
+        // masked units can't be started, not even as a dependency
+        masked: bool,
+        // managed by the user's systemd instead of the system's
+        user: bool,
+        failed: bool,
+        // masking, the journal and dependencies are only there with systemd
+        systemd: bool,
+        // the .timer unit of the same name, empty without one
+        timer: string,
+        // e.g. "next run in 3 hours"
+        timer_state: string,
+        busy: bool,
+    }
+
+    @rust-attr(derive(serde::Serialize, serde::Deserialize))
+    export struct MachineData {
         ip: string,
         hostname: string,
         user: string,
         os: string,
         online: bool,
         details: string,
+        // home directory mounted with sshfs
+        mounted: bool,
+    }
+
+    export struct LocalNodeData {
+        ipv4: string,
+        ipv6: string,
+        dns_name: string,
+        tailnet: string,
+        account: string,
+    }
+
+    export struct ProfileData {
+        id: string,
+        tailnet: string,
+        account: string,
+        active: bool,
+    }
+
+    export struct ServeHandlerData {
+        label: string,
+        protocol: string,
+        port: string,
+        path: string,
+        funnel: bool,
+    }
+
+    export struct DriveShareData {
+        name: string,
+        path: string,
+        as_user: string,
+    }
+
+    export struct LogLineData {
+        time: string,
+        message: string,
+        // syslog priority, 0 (emergency) to 7 (debug)
+        priority: int,
+    }
+
+    export struct InfoRowData {
+        label: string,
+        value: string,
+    }
+
+    export struct DerpLatencyData {
+        region: string,
+        name: string,
+        latency: string,
+        nearest: bool,
+    }
+
+    export enum ConnectionState {
+        running,
+        stopped,
+        needs-login,
+        needs-machine-auth,
+        daemon-down,
+        not-responding,
+        unknown,
+    }
+
+    export struct TourStepData {
+        target: string,
+        title: string,
+        text: string,
     }
 
     component Machine inherits Rectangle {
@@ -75,6 +246,12 @@ slint::slint! {
         in property <string> ip;
         in property <string> name;
         in property <bool> is_online;
+        in property <bool> selected;
+        in property <string> os;
+        out property <bool> hovered: touch.has-hover;
+
+        height: 20px;
+        background: selected ? #ffffff22 : transparent;
 
         HorizontalLayout {
             spacing: 5px;
@@ -101,128 +278,2596 @@ slint::slint! {
                 }
             }
 
-            Text {
-                text: name;
-                color: #ffffff;
+            HorizontalLayout {
+                spacing: 5px;
+
+                Text {
+                    text: name;
+                    color: #ffffff;
+                }
+
+                Text {
+                    text: os;
+                    color: #888888;
+                    font-size: 10px;
+                    vertical-alignment: center;
+                }
             }
         }
 
-        TouchArea {
+        touch := TouchArea {
             clicked => {
                 root.clicked();
             }
         }
     }
 
-    export component MainWindow inherits Window {
-        width: 326px;
-        height: 326px;
-        always-on-top: true;
-        title: "Tailscale";
-        icon: @image-url("imgs/tailscale-dark.svg");
+    component ShortcutsDialog inherits Rectangle {
+        callback close;
 
-        callback toggle();
-        callback copy_machine_ip(string);
+        background: #000000dd;
 
-        in property <bool> is_on;
-        in property <[MachineData]> machines: [];
-        in property <bool> copy_success: false;
+        TouchArea {
+            clicked => {
+                root.close();
+            }
+        }
 
-        VerticalLayout{
-            spacing: 5px;
+        VerticalLayout {
+            padding: 16px;
+            spacing: 6px;
+            alignment: start;
 
-            Rectangle {
-                background: #aaaaaa;
-                height: 35px;
+            Text {
+                text: "Keyboard shortcuts";
+                font-size: 14px;
+                font-weight: 700;
+                color: #ffffff;
+            }
 
-                ToggleSwitch {
-                    x: 12px;
-                    checked: is_on;
-                    text: is_on ? "tailscale running" : "tailscale stopped";
-                    toggled => {
-                        toggle();
-                    }
+            for shortcut in [
+                { key: "Space", action: "Toggle tailscale" },
+                { key: "↑ / ↓", action: "Select machine" },
+                { key: "Enter", action: "Copy selected machine's IP" },
+                { key: "F1 / ?", action: "Show this help" },
+                { key: "Esc", action: "Close dialog" },
+                { key: "Ctrl+Q", action: "Quit" },
+            ] : HorizontalLayout {
+                spacing: 12px;
+
+                Text {
+                    text: shortcut.key;
+                    width: 70px;
+                    color: #bbbbbb;
+                }
+
+                Text {
+                    text: shortcut.action;
+                    color: #ffffff;
                 }
             }
+        }
+    }
+
+    component InfoDialog inherits Rectangle {
+        callback close;
+
+        in property <string> title;
+        in property <[InfoRowData]> rows;
+
+        background: #000000dd;
+
+        TouchArea {
+            clicked => {
+                root.close();
+            }
+        }
+
+        VerticalLayout {
+            padding: 16px;
+            spacing: 6px;
+
+            Text {
+                text: root.title;
+                font-size: 14px;
+                font-weight: 700;
+                color: #ffffff;
+            }
 
             ScrollView {
                 VerticalLayout {
-                    spacing: 5px;
+                    spacing: 4px;
+                    alignment: start;
 
-                    for tile[i] in machines : Machine {
-                        ip: tile.ip;
-                        name: tile.hostname;
-                        is_online: tile.online;
+                    for row in rows : HorizontalLayout {
+                        spacing: 12px;
+
+                        Text {
+                            text: row.label;
+                            width: 110px;
+                            color: #bbbbbb;
+                            wrap: word-wrap;
+                        }
+
+                        Text {
+                            text: row.value;
+                            color: #ffffff;
+                            wrap: word-wrap;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    // newest messages on top, so following needs no scrolling
+    component LogViewer inherits Rectangle {
+        callback close;
+        // pausing or searching changed which lines to show
+        callback refresh;
+
+        in property <string> title;
+        in property <[LogLineData]> lines;
+        in-out property <bool> paused;
+        in-out property <string> filter;
+
+        background: #000000dd;
+
+        // keep clicks from reaching the window behind the viewer
+        TouchArea {}
+
+        FocusScope {
+            key-pressed(event) => {
+                if (event.text == Key.Escape) {
+                    root.close();
+                    return accept;
+                }
+                reject
+            }
+
+            VerticalLayout {
+                padding: 16px;
+                spacing: 6px;
+
+                HorizontalLayout {
+                    spacing: 8px;
+
+                    Text {
+                        text: root.title;
+                        font-size: 14px;
+                        font-weight: 700;
+                        color: #ffffff;
+                        overflow: elide;
+                        vertical-alignment: center;
+                        horizontal-stretch: 1;
+                    }
+
+                    Button {
+                        text: root.paused ? "Resume" : "Pause";
                         clicked => {
-                            root.copy_machine_ip(tile.ip);
+                            root.paused = !root.paused;
+                            root.refresh();
+                        }
+                    }
+
+                    Button {
+                        text: "Close";
+                        clicked => {
+                            root.close();
+                        }
+                    }
+                }
+
+                search := LineEdit {
+                    placeholder-text: "Search";
+                    text <=> root.filter;
+                    edited => {
+                        root.refresh();
+                    }
+                }
+
+                ScrollView {
+                    VerticalLayout {
+                        spacing: 2px;
+                        alignment: start;
+
+                        for line in lines : HorizontalLayout {
+                            spacing: 8px;
+
+                            Text {
+                                text: line.time;
+                                color: #888888;
+                            }
+
+                            Text {
+                                text: line.message;
+                                color: line.priority <= 3 ? #ff6b6b : line.priority == 4 ? #ffd166 : line.priority == 7 ? #888888 : #ffffff;
+                                wrap: word-wrap;
+                                horizontal-stretch: 1;
+                            }
                         }
                     }
                 }
             }
         }
 
-        if copy_success : Rectangle {
-            background: black;
-            height: 35px;
-            y: root.height - self.height;
+        init => {
+            search.focus();
+        }
+    }
+
+    component TourCard inherits Rectangle {
+        callback next;
+        callback skip;
+
+        in property <TourStepData> step;
+        in property <int> index;
+        in property <int> count;
+
+        background: #222222dd;
+        border-radius: 6px;
+
+        // keep clicks from reaching the window behind the card
+        TouchArea {}
+
+        VerticalLayout {
+            padding: 12px;
+            spacing: 6px;
 
             Text {
-                text: "successfully copied";
+                text: root.step.title;
+                font-weight: 700;
                 color: #ffffff;
             }
+
+            Text {
+                text: root.step.text;
+                color: #dddddd;
+                wrap: word-wrap;
+            }
+
+            HorizontalLayout {
+                spacing: 8px;
+
+                Text {
+                    text: (root.index + 1) + " / " + root.count;
+                    color: #888888;
+                    vertical-alignment: center;
+                }
+
+                Rectangle {}
+
+                if root.index + 1 < root.count : Button {
+                    text: "Skip";
+                    clicked => {
+                        root.skip();
+                    }
+                }
+
+                Button {
+                    text: root.index + 1 < root.count ? "Next" : "Done";
+                    primary: true;
+                    clicked => {
+                        root.next();
+                    }
+                }
+            }
         }
     }
-}
 
-pub struct TailscaleState {
-    pub enabled: bool,
-    pub machines: Vec<MachineData>,
-}
+    component PromptDialog inherits Rectangle {
+        callback accepted(string);
+        callback cancelled;
 
-fn main() {
-    use slint::Model;
+        in property <string> title;
+        in property <string> placeholder;
+        in property <string> action: "OK";
 
-    let main_window = MainWindow::new().unwrap();
-    update_tailscale_state(&main_window);
+        background: #000000dd;
 
-    let main_window_weak = main_window.as_weak();
+        // keep clicks from reaching the window behind the dialog
+        TouchArea {}
 
-    // toggle tailscale
-    let main_window_weak_for_toggle = main_window_weak.clone();
-    main_window.on_toggle(move || {
-        let _ = Tailscale::toggle();
-        let main_window = main_window_weak_for_toggle.unwrap();
-        update_tailscale_state(&main_window);
-    });
+        FocusScope {
+            key-pressed(event) => {
+                if (event.text == Key.Escape) {
+                    root.cancelled();
+                    return accept;
+                }
+                reject
+            }
 
-    // copy machine ip
-    let main_window_weak_for_copy = main_window_weak.clone();
-    main_window.on_copy_machine_ip(move |ip| {
-        let res = cli_clipboard::set_contents(ip.into());
+            VerticalLayout {
+                padding: 16px;
+                spacing: 8px;
+                alignment: center;
 
-        if res.is_err() {
-            return;
+                Text {
+                    text: root.title;
+                    font-weight: 700;
+                    color: #ffffff;
+                }
+
+                input := LineEdit {
+                    placeholder-text: root.placeholder;
+                    accepted(text) => {
+                        root.accepted(text);
+                    }
+                }
+
+                HorizontalLayout {
+                    alignment: end;
+                    spacing: 8px;
+
+                    Button {
+                        text: "Cancel";
+                        clicked => {
+                            root.cancelled();
+                        }
+                    }
+
+                    Button {
+                        text: root.action;
+                        primary: true;
+                        clicked => {
+                            root.accepted(input.text);
+                        }
+                    }
+                }
+            }
         }
 
-        let main_window = main_window_weak_for_copy.unwrap();
-        main_window.set_copy_success(true);
-        slint::Timer::single_shot(std::time::Duration::from_secs(1), move || {
-            main_window.set_copy_success(false);
-        });
-    });
+        init => {
+            input.focus();
+        }
+    }
 
-    main_window.run().unwrap();
-}
+    export component MainWindow inherits Window {
+        width: 326px;
+        height: 326px;
+        always-on-top: true;
+        title: "Tailscale";
+        icon: @image-url("imgs/tailscale-dark.svg");
 
-fn update_tailscale_state(main_window: &MainWindow) {
-    let enabled = Tailscale::is_enabled().unwrap_or(false);
-    main_window.set_is_on(enabled);
+        callback toggle();
+        callback log_in();
+        callback toggle_service();
+        callback toggle_unit(ServiceData);
+        callback toggle_unit_enabled(ServiceData);
+        callback toggle_unit_masked(ServiceData);
+        callback show_unit_dependencies(ServiceData);
+        callback run_unit_now(ServiceData);
+        callback show_unit_log(ServiceData);
+        callback log_refresh();
+        callback log_closed();
+        callback copy_machine_ip(string);
+        callback toggle_mount(MachineData);
+        callback switch_profile(string);
+        callback export_inventory(string);
+        callback fetch_cert();
+        callback run_netcheck();
+        callback show_dns_status();
+        callback show_metrics();
+        callback use_suggested_exit_node();
+        callback toggle_autostart();
+        callback show_usage_statistics();
+        callback run_update();
+        callback create_bugreport();
+        callback quit();
+        callback quit_and_stop_services();
+        callback serve_off(ServeHandlerData);
+        callback set_funnel(ServeHandlerData, bool);
+        callback share_port(string);
+        callback open_path(string);
+        callback open_admin_console();
+        callback drive_share(string);
+        callback drive_unshare(string);
+        callback rename_device(string);
+        callback tour_finished();
+        callback set_paused(string, bool);
 
-    let machines = match enabled {
-        false => vec![],
-        true => Tailscale::status().unwrap_or(vec![]),
-    };
+        in property <ConnectionState> state: ConnectionState.daemon-down;
+        property <bool> is_on: state == ConnectionState.running;
+        in property <[MachineData]> machines: [];
+        in property <[ServiceData]> services: [];
+        in property <[ProfileData]> profiles: [];
+        in property <LocalNodeData> local_node;
+        in property <string> key_expiry;
+        in property <string> monitor_status;
+        in property <bool> has_monitor: false;
+        in property <bool> has_schedules: false;
+        in-out property <bool> services_paused: false;
+        in-out property <bool> monitor_paused: false;
+        in-out property <bool> schedules_paused: false;
+        in property <[string]> health_warnings: [];
+        in property <[ServeHandlerData]> serve_handlers: [];
+        in property <[DriveShareData]> drive_shares: [];
+        in property <bool> copy_success: false;
+        in-out property <int> selected_machine: -1;
+        property <int> hovered_machine: -1;
+        property <bool> show_shortcuts: false;
+        property <bool> show_share_port: false;
+        property <bool> show_drive_share: false;
+        property <bool> show_rename: false;
+        in-out property <bool> show_info: false;
+        in-out property <string> info_title;
+        in-out property <[InfoRowData]> info_rows: [];
+        in-out property <bool> show_log: false;
+        in-out property <string> log_title;
+        in-out property <[LogLineData]> log_lines: [];
+        in-out property <bool> log_paused: false;
+        in-out property <string> log_filter;
+        in-out property <bool> netcheck_running: false;
+        in property <[DerpLatencyData]> derp_latencies: [];
+        in property <bool> autostart: false;
+        in property <bool> telemetry_enabled: false;
+        in property <string> version;
+        in property <string> available_update;
+        in property <bool> can_update: false;
+        in property <bool> can_export: false;
+        in property <bool> sshfs_available: false;
+        in property <bool> service_available: false;
+        in property <bool> service_active: false;
+        in-out property <bool> service_busy: false;
+        property <length> machine_row_height: 25px;
+        in property <[TourStepData]> tour_steps: [];
+        in-out property <int> tour_step: -1;
 
-    let machine_model = Rc::new(slint::VecModel::from(machines));
-    main_window.set_machines(machine_model.clone().into());
+        forward-focus: key_handler;
+
+        function end_tour() {
+            tour_step = -1;
+            key_handler.focus();
+            root.tour_finished();
+        }
+
+        // there is nothing to toggle before logging in
+        function toggle_or_log_in() {
+            if (state == ConnectionState.needs-login) {
+                root.log_in();
+            } else {
+                root.toggle();
+            }
+        }
+
+        // moves the selection and scrolls it into view
+        function select_machine(index: int) {
+            selected_machine = max(0, min(index, machines.length - 1));
+            if (selected_machine * machine_row_height < -machine_list.viewport-y) {
+                machine_list.viewport-y = -selected_machine * machine_row_height;
+            }
+            if ((selected_machine + 1) * machine_row_height > machine_list.visible-height - machine_list.viewport-y) {
+                machine_list.viewport-y = machine_list.visible-height - (selected_machine + 1) * machine_row_height;
+            }
+        }
+
+        MenuBar {
+            Menu {
+                title: "Tailscale";
+
+                if state == ConnectionState.needs-login : MenuItem {
+                    title: "Log in…";
+                    activated => {
+                        root.log_in();
+                    }
+                }
+
+                if state == ConnectionState.daemon-down && !service_available : MenuItem {
+                    title: "tailscaled is not running";
+                    enabled: false;
+                }
+
+                if state == ConnectionState.needs-machine-auth : MenuItem {
+                    title: "Waiting for admin approval, open admin console";
+                    activated => {
+                        root.open_admin_console();
+                    }
+                }
+
+                if state == ConnectionState.not-responding : MenuItem {
+                    title: "tailscaled is not responding";
+                    enabled: false;
+                }
+
+                for warning in health_warnings : MenuItem {
+                    title: "⚠ " + warning;
+                    enabled: false;
+                }
+
+                if health_warnings.length > 0 : MenuSeparator {}
+
+                if local_node.ipv4 != "" : MenuItem {
+                    title: "Copy IPv4  " + local_node.ipv4;
+                    activated => {
+                        root.copy_machine_ip(local_node.ipv4);
+                    }
+                }
+
+                if local_node.ipv6 != "" : MenuItem {
+                    title: "Copy IPv6  " + local_node.ipv6;
+                    activated => {
+                        root.copy_machine_ip(local_node.ipv6);
+                    }
+                }
+
+                if local_node.dns_name != "" : MenuItem {
+                    title: "Copy name  " + local_node.dns_name;
+                    activated => {
+                        root.copy_machine_ip(local_node.dns_name);
+                    }
+                }
+
+                if key_expiry != "" : MenuItem {
+                    title: key_expiry;
+                    enabled: false;
+                }
+
+                if monitor_status != "" : MenuItem {
+                    title: "Ping " + monitor_status;
+                    enabled: false;
+                }
+
+                MenuSeparator {}
+
+                Menu {
+                    title: "Switch account";
+
+                    for profile in profiles : MenuItem {
+                        title: (profile.active ? "✓ " : "    ") + profile.account + " (" + profile.tailnet + ")";
+                        activated => {
+                            root.switch_profile(profile.id);
+                        }
+                    }
+                }
+
+                // stopping the daemon itself, where `tailscale down` only disconnects
+                if service_available : MenuItem {
+                    title: service_busy ? "Waiting for tailscaled…" : service_active ? "Stop tailscaled service" : "Start tailscaled service";
+                    enabled: !service_busy;
+                    activated => {
+                        root.toggle_service();
+                    }
+                }
+
+                MenuItem {
+                    title: "Rename this device…";
+                    enabled: is_on;
+                    activated => {
+                        show_rename = true;
+                    }
+                }
+
+                MenuItem {
+                    title: "Open admin console";
+                    activated => {
+                        root.open_admin_console();
+                    }
+                }
+
+                MenuItem {
+                    title: "Fetch TLS certificate";
+                    enabled: local_node.dns_name != "";
+                    activated => {
+                        root.fetch_cert();
+                    }
+                }
+
+                MenuItem {
+                    title: "Use suggested exit node";
+                    enabled: is_on;
+                    activated => {
+                        root.use_suggested_exit_node();
+                    }
+                }
+
+                MenuItem {
+                    title: netcheck_running ? "Running netcheck…" : "Run netcheck";
+                    enabled: !netcheck_running;
+                    activated => {
+                        root.run_netcheck();
+                    }
+                }
+
+                MenuItem {
+                    title: "DNS configuration…";
+                    enabled: state == ConnectionState.running;
+                    activated => {
+                        root.show_dns_status();
+                    }
+                }
+
+                MenuItem {
+                    title: "Metrics…";
+                    enabled: state == ConnectionState.running || state == ConnectionState.stopped;
+                    activated => {
+                        root.show_metrics();
+                    }
+                }
+
+                Menu {
+                    title: "DERP latency";
+
+                    if derp_latencies.length == 0 : MenuItem {
+                        title: "Run netcheck to measure";
+                        enabled: false;
+                    }
+
+                    for region in derp_latencies : MenuItem {
+                        title: (region.nearest ? "✓ " : "    ") + region.latency + "  " + region.name + " (" + region.region + ")";
+                        enabled: false;
+                    }
+                }
+
+                if can_export : Menu {
+                    title: "Export inventory";
+
+                    MenuItem {
+                        title: "As CSV";
+                        activated => {
+                            root.export_inventory("csv");
+                        }
+                    }
+
+                    MenuItem {
+                        title: "As JSON";
+                        activated => {
+                            root.export_inventory("json");
+                        }
+                    }
+                }
+
+                MenuSeparator {}
+
+                MenuItem {
+                    title: "Quit";
+                    activated => {
+                        root.quit();
+                    }
+                }
+
+                MenuItem {
+                    title: service_available ? "Quit and stop Tailscale" : "Quit and disconnect";
+                    activated => {
+                        root.quit_and_stop_services();
+                    }
+                }
+            }
+
+            Menu {
+                title: "Serve";
+
+                if serve_handlers.length == 0 : MenuItem {
+                    title: "Nothing served";
+                    enabled: false;
+                }
+
+                for handler in serve_handlers : Menu {
+                    title: (handler.funnel ? "🌐 PUBLIC  " : "") + handler.label;
+
+                    // funnel only works for HTTPS handlers
+                    MenuItem {
+                        title: (handler.funnel ? "✓ " : "    ") + "Funnel to the public internet";
+                        enabled: handler.protocol == "https";
+                        activated => {
+                            root.set_funnel(handler, !handler.funnel);
+                        }
+                    }
+
+                    MenuItem {
+                        title: "Turn off";
+                        activated => {
+                            root.serve_off(handler);
+                        }
+                    }
+                }
+
+                MenuSeparator {}
+
+                MenuItem {
+                    title: "Share local port…";
+                    enabled: is_on;
+                    activated => {
+                        show_share_port = true;
+                    }
+                }
+            }
+
+            Menu {
+                title: "Machines";
+
+                if machines.length == 0 : MenuItem {
+                    title: "No machines";
+                    enabled: false;
+                }
+
+                for machine in machines : Menu {
+                    title: (machine.mounted ? "📂 " : "") + machine.hostname + (machine.online ? "" : " (offline)");
+
+                    MenuItem {
+                        title: "Copy IP  " + machine.ip;
+                        activated => {
+                            root.copy_machine_ip(machine.ip);
+                        }
+                    }
+
+                    if sshfs_available : MenuItem {
+                        title: machine.mounted ? "Unmount ~/mnt/" + machine.hostname : "Mount home via sshfs";
+                        enabled: machine.online || machine.mounted;
+                        activated => {
+                            root.toggle_mount(machine);
+                        }
+                    }
+                }
+            }
+
+            Menu {
+                title: "Taildrive";
+
+                if drive_shares.length == 0 : MenuItem {
+                    title: "No shares";
+                    enabled: false;
+                }
+
+                for share in drive_shares : Menu {
+                    title: share.name + "  " + share.path;
+
+                    MenuItem {
+                        title: "Open";
+                        activated => {
+                            root.open_path(share.path);
+                        }
+                    }
+
+                    MenuItem {
+                        title: "Remove share";
+                        activated => {
+                            root.drive_unshare(share.name);
+                        }
+                    }
+                }
+
+                MenuSeparator {}
+
+                // Taildrive is served over WebDAV on the Tailscale service address
+                MenuItem {
+                    title: "Browse Taildrive";
+                    enabled: is_on;
+                    activated => {
+                        root.open_path("dav://100.100.100.100:8080");
+                    }
+                }
+
+                MenuItem {
+                    title: "Share folder…";
+                    enabled: is_on;
+                    activated => {
+                        show_drive_share = true;
+                    }
+                }
+            }
+
+            if services.length > 0 : Menu {
+                title: services_paused ? "Services (paused)" : "Services";
+
+                for service in services : Menu {
+                    enabled: !services_paused;
+                    title: (service.failed ? "✗ " : service.active ? "● " : "○ ") + service.unit + (service.user ? " (user)" : "") + (service.masked ? " (masked)" : "");
+
+                    MenuItem {
+                        title: service.state;
+                        enabled: false;
+                    }
+
+                    MenuItem {
+                        title: service.busy ? "Working…" : service.active ? "Stop" : "Start";
+                        enabled: !service.busy && (service.active || !service.masked);
+                        activated => {
+                            root.toggle_unit(service);
+                        }
+                    }
+
+                    if service.can_enable : MenuItem {
+                        title: (service.enabled ? "✓ " : "    ") + "Start on boot";
+                        enabled: !service.busy;
+                        activated => {
+                            root.toggle_unit_enabled(service);
+                        }
+                    }
+
+                    if service.timer != "" : MenuItem {
+                        title: service.timer + ", " + service.timer_state;
+                        enabled: false;
+                    }
+
+                    if service.timer != "" : MenuItem {
+                        title: "Run now";
+                        enabled: !service.busy && !service.active && !service.masked;
+                        activated => {
+                            root.run_unit_now(service);
+                        }
+                    }
+
+                    if service.systemd : MenuItem {
+                        title: "Follow log…";
+                        activated => {
+                            root.show_unit_log(service);
+                        }
+                    }
+
+                    if service.systemd : MenuItem {
+                        title: "Dependencies…";
+                        activated => {
+                            root.show_unit_dependencies(service);
+                        }
+                    }
+
+                    if service.systemd : MenuSeparator {}
+
+                    if service.systemd : MenuItem {
+                        title: service.masked ? "Unmask" : "Mask, to keep anything from starting it";
+                        enabled: !service.busy;
+                        activated => {
+                            root.toggle_unit_masked(service);
+                        }
+                    }
+                }
+            }
+
+            Menu {
+                title: "Settings";
+
+                MenuItem {
+                    title: (autostart ? "✓ " : "    ") + "Autostart on login";
+                    activated => {
+                        root.toggle_autostart();
+                    }
+                }
+
+                // e.g. while a misbehaving service manager keeps failing
+                if services.length > 0 || has_monitor || has_schedules : MenuSeparator {}
+
+                if services.length > 0 : MenuItem {
+                    title: (services_paused ? "✓ " : "    ") + "Pause Services integration";
+                    activated => {
+                        root.set_paused("services", !services_paused);
+                    }
+                }
+
+                if has_monitor : MenuItem {
+                    title: (monitor_paused ? "✓ " : "    ") + "Pause peer monitor";
+                    activated => {
+                        root.set_paused("monitor", !monitor_paused);
+                    }
+                }
+
+                if has_schedules : MenuItem {
+                    title: (schedules_paused ? "✓ " : "    ") + "Pause peer schedules";
+                    activated => {
+                        root.set_paused("schedules", !schedules_paused);
+                    }
+                }
+            }
+
+            Menu {
+                title: "Help";
+
+                if version != "" : MenuItem {
+                    title: "Tailscale " + version;
+                    enabled: false;
+                }
+
+                if available_update != "" : MenuItem {
+                    title: can_update ? "Update to " + available_update : "Update available: " + available_update;
+                    enabled: can_update;
+                    activated => {
+                        root.run_update();
+                    }
+                }
+
+                MenuSeparator {}
+
+                MenuItem {
+                    title: "Generate bug report";
+                    activated => {
+                        root.create_bugreport();
+                    }
+                }
+
+                MenuItem {
+                    title: "Keyboard shortcuts";
+                    activated => {
+                        show_shortcuts = true;
+                    }
+                }
+
+                MenuItem {
+                    title: "Take the tour";
+                    activated => {
+                        tour_step = 0;
+                    }
+                }
+
+                if telemetry_enabled : MenuItem {
+                    title: "Usage statistics";
+                    activated => {
+                        root.show_usage_statistics();
+                    }
+                }
+            }
+        }
+
+        key_handler := FocusScope {
+            key-pressed(event) => {
+                if (event.text == Key.Escape && tour_step >= 0) {
+                    end_tour();
+                    return accept;
+                }
+                if (event.text == Key.Escape && (show_shortcuts || show_info)) {
+                    show_shortcuts = false;
+                    show_info = false;
+                    return accept;
+                }
+                if (event.text == Key.F1 || event.text == "?") {
+                    show_shortcuts = !show_shortcuts;
+                    return accept;
+                }
+                if (event.modifiers.control && event.text == "q") {
+                    root.quit();
+                    return accept;
+                }
+                if (event.text == " ") {
+                    toggle_or_log_in();
+                    return accept;
+                }
+                if (event.text == Key.DownArrow) {
+                    select_machine(selected_machine + 1);
+                    return accept;
+                }
+                if (event.text == Key.UpArrow) {
+                    select_machine(selected_machine - 1);
+                    return accept;
+                }
+                if (event.text == Key.Return && selected_machine >= 0 && selected_machine < machines.length) {
+                    root.copy_machine_ip(machines[selected_machine].ip);
+                    return accept;
+                }
+                reject
+            }
+        }
+
+        VerticalLayout{
+            spacing: 5px;
+
+            header := Rectangle {
+                background: #aaaaaa;
+                height: 35px;
+
+                ToggleSwitch {
+                    x: 12px;
+                    checked: is_on;
+                    text: state == ConnectionState.running ? "tailscale running"
+                        : state == ConnectionState.stopped ? "tailscale stopped"
+                        : state == ConnectionState.needs-login ? "login required"
+                        : state == ConnectionState.needs-machine-auth ? "waiting for admin approval"
+                        : state == ConnectionState.not-responding ? "backend not responding"
+                        : state == ConnectionState.unknown ? "unknown state"
+                        : "tailscaled not running";
+                    toggled => {
+                        toggle_or_log_in();
+                    }
+                }
+
+                if local_node.account != "" : VerticalLayout {
+                    x: parent.width - self.width - 12px;
+                    alignment: center;
+
+                    Text {
+                        text: local_node.account;
+                        font-size: 11px;
+                        horizontal-alignment: right;
+                    }
+
+                    Text {
+                        text: local_node.tailnet;
+                        font-size: 10px;
+                        color: #444444;
+                        horizontal-alignment: right;
+                    }
+                }
+            }
+
+            machine_list := ScrollView {
+                VerticalLayout {
+                    spacing: machine_row_height - 20px;
+
+                    for tile[i] in machines : Machine {
+                        ip: tile.ip;
+                        name: tile.hostname;
+                        is_online: tile.online;
+                        os: tile.os;
+                        selected: i == selected_machine;
+                        changed hovered => {
+                            if (self.hovered) {
+                                hovered_machine = i;
+                            } else if (hovered_machine == i) {
+                                hovered_machine = -1;
+                            }
+                        }
+                        clicked => {
+                            selected_machine = i;
+                            key_handler.focus();
+                            root.copy_machine_ip(tile.ip);
+                        }
+                    }
+                }
+            }
+        }
+
+        // connection details of the hovered machine, e.g. "linux · active; relay \"fra\""
+        if hovered_machine >= 0 && hovered_machine < machines.length && !copy_success : Rectangle {
+            property <MachineData> machine: machines[hovered_machine];
+
+            background: #222222dd;
+            border-radius: 4px;
+            x: 6px;
+            y: root.height - self.height - 6px;
+            width: root.width - 12px;
+            height: 22px;
+
+            Text {
+                text: machine.os + (machine.details == "" || machine.details == "-" ? "" : " · " + machine.details);
+                color: #ffffff;
+                font-size: 11px;
+                overflow: elide;
+            }
+        }
+
+        if copy_success : Rectangle {
+            background: black;
+            height: 35px;
+            y: root.height - self.height;
+
+            Text {
+                text: "successfully copied";
+                color: #ffffff;
+            }
+        }
+
+        if show_share_port : PromptDialog {
+            title: "Share local port";
+            placeholder: "e.g. 3000";
+            action: "Share";
+            accepted(port) => {
+                show_share_port = false;
+                key_handler.focus();
+                root.share_port(port);
+            }
+            cancelled => {
+                show_share_port = false;
+                key_handler.focus();
+            }
+        }
+
+        if show_drive_share : PromptDialog {
+            title: "Share folder with the tailnet";
+            placeholder: "/home/me/Documents";
+            action: "Share";
+            accepted(path) => {
+                show_drive_share = false;
+                key_handler.focus();
+                root.drive_share(path);
+            }
+            cancelled => {
+                show_drive_share = false;
+                key_handler.focus();
+            }
+        }
+
+        if show_rename : PromptDialog {
+            title: "Rename this device";
+            placeholder: "e.g. work-laptop";
+            action: "Rename";
+            accepted(name) => {
+                show_rename = false;
+                key_handler.focus();
+                root.rename_device(name);
+            }
+            cancelled => {
+                show_rename = false;
+                key_handler.focus();
+            }
+        }
+
+        if show_info : InfoDialog {
+            title: info_title;
+            rows: info_rows;
+            close => {
+                show_info = false;
+                key_handler.focus();
+            }
+        }
+
+        if show_log : LogViewer {
+            title: log_title;
+            lines: log_lines;
+            paused <=> log_paused;
+            filter <=> log_filter;
+            refresh => {
+                root.log_refresh();
+            }
+            close => {
+                show_log = false;
+                root.log_closed();
+                key_handler.focus();
+            }
+        }
+
+        if show_shortcuts : ShortcutsDialog {
+            close => {
+                show_shortcuts = false;
+                key_handler.focus();
+            }
+        }
+
+        if tour_step >= 0 && tour_step < tour_steps.length : Rectangle {
+            property <TourStepData> step: tour_steps[tour_step];
+
+            background: #00000066;
+
+            // outlines the part of the window the step is about
+            if step.target != "" : Rectangle {
+                x: step.target == "toggle" ? header.x : machine_list.x;
+                y: step.target == "toggle" ? header.y : machine_list.y;
+                width: step.target == "toggle" ? header.width : machine_list.width;
+                height: step.target == "toggle" ? header.height : machine_list.height;
+                border-color: #4c9aff;
+                border-width: 2px;
+                border-radius: 4px;
+            }
+
+            TourCard {
+                x: 8px;
+                y: step.target == "machines" ? 8px : parent.height - self.height - 8px;
+                width: parent.width - 16px;
+                step: step;
+                index: tour_step;
+                count: tour_steps.length;
+                next => {
+                    if (tour_step + 1 < tour_steps.length) {
+                        tour_step += 1;
+                    } else {
+                        end_tour();
+                    }
+                }
+                skip => {
+                    end_tour();
+                }
+            }
+        }
+    }
+}
+
+fn main() {
+    // subcommands setting tailslint up instead of opening the window
+    #[cfg(target_os = "linux")]
+    match std::env::args().nth(1).as_deref() {
+        Some("install-service") => {
+            let config = Config::load().unwrap_or_default();
+            let command_timeout = std::time::Duration::from_secs(config.command_timeout_secs);
+            match user_service::install(command_timeout) {
+                Ok(path) => println!("Installed and started {}", path.display()),
+                Err(e) => {
+                    eprintln!("Failed to install the service: {e}");
+                    std::process::exit(1);
+                }
+            }
+            return;
+        }
+        Some("uninstall-service") => {
+            if let Err(e) = user_service::uninstall() {
+                eprintln!("Failed to uninstall the service: {e}");
+                std::process::exit(1);
+            }
+            return;
+        }
+        Some("install-polkit-rule") => {
+            let units: Vec<String> = std::env::args().skip(2).collect();
+            match polkit_rule::install(&units) {
+                Ok(path) => println!("Wrote {}", path.display()),
+                Err(e) => {
+                    eprintln!("Failed to install the polkit rule: {e}");
+                    std::process::exit(1);
+                }
+            }
+            return;
+        }
+        Some("uninstall-polkit-rule") => {
+            if let Err(e) = polkit_rule::uninstall() {
+                eprintln!("Failed to remove the polkit rule: {e}");
+                std::process::exit(1);
+            }
+            return;
+        }
+        Some(command @ ("install-sudoers" | "install-doas-rule")) => {
+            // the rules name the commands of the configured init system
+            if let Ok(Config {
+                init_system: Some(init),
+                ..
+            }) = Config::load()
+            {
+                service::set_init_system(init);
+            }
+            let units: Vec<String> = std::env::args().skip(2).collect();
+            let result = match command {
+                "install-sudoers" => sudoers::install_sudoers(&units),
+                _ => sudoers::install_doas_rule(&units),
+            };
+            match result {
+                Ok(path) => println!("Wrote {}", path.display()),
+                Err(e) => {
+                    eprintln!("Failed to install the rule: {e}");
+                    std::process::exit(1);
+                }
+            }
+            return;
+        }
+        Some(command @ ("uninstall-sudoers" | "uninstall-doas-rule")) => {
+            let result = match command {
+                "uninstall-sudoers" => sudoers::uninstall_sudoers(),
+                _ => sudoers::uninstall_doas_rule(),
+            };
+            if let Err(e) = result {
+                eprintln!("Failed to remove the rule: {e}");
+                std::process::exit(1);
+            }
+            return;
+        }
+        _ => {}
+    }
+
+    let config = Config::load().unwrap_or_else(|e| {
+        eprintln!("{e}, using defaults");
+        Config::default()
+    });
+
+    telemetry::init(config.telemetry && !config.low_memory);
+    NOTIFY_HEALTH_WARNINGS.store(config.notify_health_warnings, Ordering::Relaxed);
+    KEY_EXPIRY_WARNING_DAYS.store(config.key_expiry_warning_days, Ordering::Relaxed);
+    process::kill_all_on_panic();
+    process::set_timeout(std::time::Duration::from_secs(config.command_timeout_secs));
+    #[cfg(target_os = "linux")]
+    if let Some(init) = config.init_system {
+        service::set_init_system(init);
+    }
+    #[cfg(target_os = "linux")]
+    if let Some(escalation) = config.escalation {
+        service::set_escalation(escalation);
+    }
+
+    if let Some(seed) = demo::seed_from_args(std::env::args()) {
+        demo::start(seed);
+    }
+
+    notification::settle(std::time::Duration::from_secs(
+        config.notification_settle_secs,
+    ));
+    let main_window = MainWindow::new().unwrap();
+    update_tailscale_state(&main_window);
+    #[cfg(all(unix, feature = "localapi"))]
+    if !config.low_memory {
+        watch::start(main_window.as_weak());
+    }
+    #[cfg(all(unix, feature = "scripting"))]
+    fifo::start(main_window.as_weak());
+    main_window.set_can_export(cfg!(feature = "inventory"));
+    if let Some(peer) = config.monitor_peer.clone()
+        && !config.low_memory
+    {
+        monitor::start(main_window.as_weak(), peer, config.monitor_threshold_ms);
+        main_window.set_has_monitor(true);
+    }
+
+    main_window.set_tour_steps(Rc::new(slint::VecModel::from(tour::steps())).into());
+    if tour::is_first_run() {
+        main_window.set_tour_step(0);
+    }
+
+    let main_window_weak = main_window.as_weak();
+
+    // alert about peers deviating from their expected online schedule
+    #[cfg(feature = "schedules")]
+    if !config.peer_schedules.is_empty() && !config.low_memory {
+        schedule::start(config.peer_schedules.clone());
+        main_window.set_has_schedules(true);
+    }
+
+    // toggle tailscale
+    let main_window_weak_for_toggle = main_window_weak.clone();
+    let up_flags = config.up_flags.clone();
+    main_window.on_toggle(move || {
+        telemetry::record("toggle");
+        // waits for the state to change, which takes a moment
+        let main_window_weak = main_window_weak_for_toggle.clone();
+        let up_flags = up_flags.clone();
+        thread::spawn(move || {
+            if let Err(e) = Tailscale::toggle(up_flags.as_deref()) {
+                notification::send("Failed to connect", &e.to_string());
+            }
+            let _ = main_window_weak.upgrade_in_event_loop(|main_window| {
+                update_tailscale_state(&main_window);
+            });
+        });
+    });
+
+    // log in, tailscaled hands out a URL to authenticate at in the browser
+    let main_window_weak_for_login = main_window_weak.clone();
+    main_window.on_log_in(move || {
+        telemetry::record("log_in");
+        let main_window_weak = main_window_weak_for_login.clone();
+        thread::spawn(move || {
+            match Tailscale::login() {
+                Ok(Some(url)) => {
+                    if let Err(e) = desktop::open(&url) {
+                        notification::send("Log in at", &format!("{url}\n{e}"));
+                    }
+                }
+                Ok(None) => {}
+                Err(e) => notification::send("Failed to log in", &e.to_string()),
+            }
+            let _ = main_window_weak.upgrade_in_event_loop(|main_window| {
+                update_tailscale_state(&main_window);
+            });
+        });
+    });
+
+    // copy machine ip
+    let main_window_weak_for_copy = main_window_weak.clone();
+    main_window.on_copy_machine_ip(move |ip| {
+        telemetry::record("copy_machine_ip");
+        let res = cli_clipboard::set_contents(ip.into());
+
+        if res.is_err() {
+            return;
+        }
+
+        let main_window = main_window_weak_for_copy.unwrap();
+        main_window.set_copy_success(true);
+        slint::Timer::single_shot(std::time::Duration::from_secs(1), move || {
+            main_window.set_copy_success(false);
+        });
+    });
+
+    // switch login profile
+    let main_window_weak_for_switch = main_window_weak.clone();
+    main_window.on_switch_profile(move |id| {
+        telemetry::record("switch_profile");
+        // reconnects with the other profile, which takes a moment
+        let main_window_weak = main_window_weak_for_switch.clone();
+        thread::spawn(move || {
+            if let Err(e) = Tailscale::switch(&id) {
+                notification::send("Failed to switch profile", &e.to_string());
+            }
+            let _ = main_window_weak.upgrade_in_event_loop(|main_window| {
+                update_tailscale_state(&main_window);
+            });
+        });
+    });
+
+    // export the peer list
+    #[cfg(feature = "inventory")]
+    let export_dir = config.export_dir();
+    #[cfg(feature = "inventory")]
+    main_window.on_export_inventory(move |format| {
+        telemetry::record("export_inventory");
+        let Some(format) = inventory::Format::from_name(&format) else {
+            return;
+        };
+
+        let export_dir = export_dir.clone();
+        thread::spawn(move || match inventory::export(format, &export_dir) {
+            Ok(path) => notification::send("Inventory exported", &path.display().to_string()),
+            Err(e) => notification::send("Inventory export failed", &e.to_string()),
+        });
+    });
+
+    // fetch a TLS certificate for this machine, ACME can take a while
+    let main_window_weak_for_cert = main_window_weak.clone();
+    let cert_dir = config.cert_dir();
+    main_window.on_fetch_cert(move || {
+        telemetry::record("fetch_cert");
+        let main_window = main_window_weak_for_cert.unwrap();
+        let domain = main_window.get_local_node().dns_name.to_string();
+        let cert_dir = cert_dir.clone();
+
+        thread::spawn(move || match Tailscale::cert(&domain, &cert_dir) {
+            Ok(path) => notification::send("TLS certificate fetched", &path.display().to_string()),
+            Err(e) => notification::send("Failed to fetch TLS certificate", &e.to_string()),
+        });
+    });
+
+    // pick the lowest latency exit node
+    let main_window_weak_for_exit_node = main_window_weak.clone();
+    main_window.on_use_suggested_exit_node(move || {
+        telemetry::record("use_suggested_exit_node");
+        let main_window_weak = main_window_weak_for_exit_node.clone();
+        thread::spawn(move || {
+            let result = Tailscale::suggested_exit_node().and_then(|node| match node {
+                Some(node) => Tailscale::set_exit_node(&node).map(|_| Some(node)),
+                None => Ok(None),
+            });
+
+            match result {
+                Ok(Some(node)) => notification::send("Exit node selected", &node),
+                Ok(None) => notification::send("No exit node available", ""),
+                Err(e) => notification::send("Failed to select exit node", &e.to_string()),
+            }
+            let _ = main_window_weak.upgrade_in_event_loop(|main_window| {
+                update_tailscale_state(&main_window);
+            });
+        });
+    });
+
+    // run netcheck in the background, it takes a few seconds
+    let main_window_weak_for_netcheck = main_window_weak.clone();
+    main_window.on_run_netcheck(move || {
+        telemetry::record("run_netcheck");
+        let main_window = main_window_weak_for_netcheck.unwrap();
+        main_window.set_netcheck_running(true);
+
+        let main_window_weak = main_window.as_weak();
+        thread::spawn(move || {
+            let result = Tailscale::netcheck();
+            let _ = main_window_weak.upgrade_in_event_loop(move |main_window| {
+                main_window.set_netcheck_running(false);
+                match result {
+                    Ok(report) => {
+                        let latencies = Rc::new(slint::VecModel::from(report.derp_latencies));
+                        main_window.set_derp_latencies(latencies.into());
+                        show_info(&main_window, "Netcheck report", report.rows);
+                    }
+                    Err(e) => notification::send("Netcheck failed", &e.to_string()),
+                }
+            });
+        });
+    });
+
+    // show how Tailscale set up DNS, e.g. when name resolution broke
+    let main_window_weak_for_dns = main_window_weak.clone();
+    main_window.on_show_dns_status(move || {
+        telemetry::record("show_dns_status");
+        let main_window_weak = main_window_weak_for_dns.clone();
+        thread::spawn(move || {
+            let result = Tailscale::dns_status();
+            let _ = main_window_weak.upgrade_in_event_loop(move |main_window| match result {
+                Ok(rows) => show_info(&main_window, "DNS configuration", rows),
+                Err(e) => notification::send("Failed to read DNS configuration", &e.to_string()),
+            });
+        });
+    });
+
+    // show tailscaled's counters, e.g. dropped packets and DERP traffic
+    let main_window_weak_for_metrics = main_window_weak.clone();
+    main_window.on_show_metrics(move || {
+        telemetry::record("show_metrics");
+        let main_window_weak = main_window_weak_for_metrics.clone();
+        thread::spawn(move || {
+            let result = Tailscale::metrics();
+            let _ = main_window_weak.upgrade_in_event_loop(move |main_window| match result {
+                Ok(rows) => show_info(&main_window, "Metrics", rows),
+                Err(e) => notification::send("Failed to read metrics", &e.to_string()),
+            });
+        });
+    });
+
+    // turn off a single serve handler
+    let main_window_weak_for_serve_off = main_window_weak.clone();
+    main_window.on_serve_off(move |handler| {
+        telemetry::record("serve_off");
+        let main_window_weak = main_window_weak_for_serve_off.clone();
+        thread::spawn(move || {
+            if let Err(e) = Tailscale::serve_off(&handler) {
+                notification::send("Failed to stop serving", &e.to_string());
+            }
+            let _ = main_window_weak.upgrade_in_event_loop(|main_window| {
+                update_tailscale_state(&main_window);
+            });
+        });
+    });
+
+    // expose a serve handler to the public internet
+    let main_window_weak_for_funnel = main_window_weak.clone();
+    main_window.on_set_funnel(move |handler, on| {
+        telemetry::record("set_funnel");
+        let main_window_weak = main_window_weak_for_funnel.clone();
+        thread::spawn(move || {
+            if let Err(e) = Tailscale::set_funnel(&handler, on) {
+                notification::send("Failed to change funnel", &e.to_string());
+            }
+            let _ = main_window_weak.upgrade_in_event_loop(|main_window| {
+                update_tailscale_state(&main_window);
+            });
+        });
+    });
+
+    // share a local port with the tailnet
+    let main_window_weak_for_share = main_window_weak.clone();
+    main_window.on_share_port(move |port| {
+        telemetry::record("share_port");
+        let Ok(port) = port.trim().parse::<u16>() else {
+            notification::send("Invalid port", &format!("\"{port}\" is not a port number"));
+            return;
+        };
+
+        let main_window_weak = main_window_weak_for_share.clone();
+        thread::spawn(move || {
+            if let Err(e) = Tailscale::serve_port(port) {
+                notification::send("Failed to share port", &e.to_string());
+            }
+            let _ = main_window_weak.upgrade_in_event_loop(|main_window| {
+                update_tailscale_state(&main_window);
+            });
+        });
+    });
+
+    main_window.on_open_path(|path| {
+        telemetry::record("open_path");
+        if let Err(e) = desktop::open(&path) {
+            notification::send("Failed to open", &e.to_string());
+        }
+    });
+
+    let admin_url = config.admin_url.clone();
+    main_window.on_open_admin_console(move || {
+        telemetry::record("open_admin_console");
+        if let Err(e) = desktop::open(&admin_url) {
+            notification::send("Failed to open admin console", &e.to_string());
+        }
+    });
+
+    // share a directory via taildrive, named after the directory
+    let main_window_weak_for_drive_share = main_window_weak.clone();
+    main_window.on_drive_share(move |path| {
+        telemetry::record("drive_share");
+        let path = path.trim().to_string();
+        let main_window_weak = main_window_weak_for_drive_share.clone();
+        thread::spawn(move || {
+            if let Err(e) = Tailscale::drive_share(&drive_share_name(&path), &path) {
+                notification::send("Failed to share folder", &e.to_string());
+            }
+            let _ = main_window_weak.upgrade_in_event_loop(|main_window| {
+                update_tailscale_state(&main_window);
+            });
+        });
+    });
+
+    // stop sharing a directory
+    let main_window_weak_for_drive_unshare = main_window_weak.clone();
+    main_window.on_drive_unshare(move |name| {
+        telemetry::record("drive_unshare");
+        let main_window_weak = main_window_weak_for_drive_unshare.clone();
+        thread::spawn(move || {
+            if let Err(e) = Tailscale::drive_unshare(&name) {
+                notification::send("Failed to remove share", &e.to_string());
+            }
+            let _ = main_window_weak.upgrade_in_event_loop(|main_window| {
+                update_tailscale_state(&main_window);
+            });
+        });
+    });
+
+    // start or stop the tailscaled service, systemd may wait for a polkit prompt
+    #[cfg(target_os = "linux")]
+    {
+        main_window.set_service_available(
+            !demo::is_active() && service::Service::tailscaled().is_installed(),
+        );
+        let main_window_weak_for_service = main_window_weak.clone();
+        main_window.on_toggle_service(move || {
+            telemetry::record("toggle_service");
+            let main_window = main_window_weak_for_service.unwrap();
+            let stop = main_window.get_service_active();
+            main_window.set_service_busy(true);
+
+            let main_window_weak = main_window_weak_for_service.clone();
+            thread::spawn(move || {
+                let service = service::Service::tailscaled();
+                let result = match stop {
+                    true => service.stop(),
+                    false => service.start(),
+                };
+                match result {
+                    // the unit counts as active before tailscaled listens on its socket
+                    Ok(()) if !stop => {
+                        retry::until(SETTLE_TIMEOUT, || {
+                            Tailscale::state() != TailscaleState::DaemonDown
+                        });
+                    }
+                    Ok(()) => {
+                        retry::until(SETTLE_TIMEOUT, || !service.is_active());
+                    }
+                    Err(e) => notification::send("Failed to control tailscaled", &e.to_string()),
+                }
+                let _ = main_window_weak.upgrade_in_event_loop(|main_window| {
+                    main_window.set_service_busy(false);
+                    update_tailscale_state(&main_window);
+                });
+            });
+        });
+    }
+    update_tailscale_state(&main_window);
+
+    // start or stop the units listed in the config, refreshed every few seconds
+    // since nothing announces their changes
+    #[cfg(target_os = "linux")]
+    let services_timer = slint::Timer::default();
+    #[cfg(target_os = "linux")]
+    if !config.services.is_empty() {
+        let units = config.services.clone();
+        update_services(&main_window, &units);
+        let main_window_weak_for_services = main_window_weak.clone();
+        if !config.low_memory {
+            services_timer.start(
+                slint::TimerMode::Repeated,
+                std::time::Duration::from_secs(10),
+                move || {
+                    let main_window = main_window_weak_for_services.unwrap();
+                    update_services(&main_window, &units);
+                },
+            );
+        }
+
+        let main_window_weak_for_unit = main_window_weak.clone();
+        main_window.on_toggle_unit(move |unit| {
+            telemetry::record("toggle_unit");
+            let main_window = main_window_weak_for_unit.unwrap();
+            set_service_busy(&main_window, &unit, true);
+
+            let main_window_weak = main_window_weak_for_unit.clone();
+            thread::spawn(move || {
+                let service = unit_service(&unit);
+                let result = match unit.active {
+                    true => service.stop(),
+                    false => service.start(),
+                };
+                match result {
+                    Ok(()) => {
+                        retry::until(SETTLE_TIMEOUT, || service.is_active() != unit.active);
+                    }
+                    Err(e) => notification::send(
+                        &format!("Failed to control {}", unit.unit),
+                        &e.to_string(),
+                    ),
+                }
+                let _ = main_window_weak.upgrade_in_event_loop(move |main_window| {
+                    set_service_busy(&main_window, &unit, false);
+                    refresh_services(&main_window);
+                });
+            });
+        });
+
+        let main_window_weak_for_enable = main_window_weak.clone();
+        main_window.on_toggle_unit_enabled(move |unit| {
+            telemetry::record("toggle_unit_enabled");
+            let main_window = main_window_weak_for_enable.unwrap();
+            set_service_busy(&main_window, &unit, true);
+
+            let main_window_weak = main_window_weak_for_enable.clone();
+            thread::spawn(move || {
+                let service = unit_service(&unit);
+                if let Err(e) = service.set_enabled(!unit.enabled) {
+                    notification::send(&format!("Failed to change {}", unit.unit), &e.to_string());
+                }
+                let _ = main_window_weak.upgrade_in_event_loop(move |main_window| {
+                    set_service_busy(&main_window, &unit, false);
+                    refresh_services(&main_window);
+                });
+            });
+        });
+
+        let main_window_weak_for_mask = main_window_weak.clone();
+        main_window.on_toggle_unit_masked(move |unit| {
+            telemetry::record("toggle_unit_masked");
+            let main_window = main_window_weak_for_mask.unwrap();
+            set_service_busy(&main_window, &unit, true);
+
+            let main_window_weak = main_window_weak_for_mask.clone();
+            thread::spawn(move || {
+                let service = unit_service(&unit);
+                if let Err(e) = service.set_masked(!unit.masked) {
+                    notification::send(&format!("Failed to change {}", unit.unit), &e.to_string());
+                }
+                let _ = main_window_weak.upgrade_in_event_loop(move |main_window| {
+                    set_service_busy(&main_window, &unit, false);
+                    refresh_services(&main_window);
+                });
+            });
+        });
+
+        // start a timer's service ahead of its schedule, it usually stops again
+        // by itself once done
+        let main_window_weak_for_run = main_window_weak.clone();
+        main_window.on_run_unit_now(move |unit| {
+            telemetry::record("run_unit_now");
+            let main_window = main_window_weak_for_run.unwrap();
+            set_service_busy(&main_window, &unit, true);
+
+            let main_window_weak = main_window_weak_for_run.clone();
+            thread::spawn(move || {
+                if let Err(e) = unit_service(&unit).start() {
+                    notification::send(&format!("Failed to run {}", unit.unit), &e.to_string());
+                }
+                let _ = main_window_weak.upgrade_in_event_loop(move |main_window| {
+                    set_service_busy(&main_window, &unit, false);
+                    refresh_services(&main_window);
+                });
+            });
+        });
+
+        // follow a unit's journal until the viewer is closed
+        let log_follower: Rc<RefCell<Option<journal::Follower>>> = Rc::new(RefCell::new(None));
+        let log_entries = Arc::new(Mutex::new(VecDeque::new()));
+        let max_log_lines = match config.low_memory {
+            true => LOW_MEMORY_LOG_LINES,
+            false => MAX_LOG_LINES,
+        };
+
+        let main_window_weak_for_log = main_window_weak.clone();
+        let log_follower_for_show = log_follower.clone();
+        let log_entries_for_show = log_entries.clone();
+        main_window.on_show_unit_log(move |unit| {
+            telemetry::record("show_unit_log");
+            let main_window = main_window_weak_for_log.unwrap();
+            // stop following the previous unit before its lines are dropped
+            log_follower_for_show.borrow_mut().take();
+            log_entries_for_show.lock().unwrap().clear();
+
+            let entries = log_entries_for_show.clone();
+            let main_window_weak = main_window_weak_for_log.clone();
+            let pending = Arc::new(AtomicBool::new(false));
+            let follower =
+                journal::follow(&unit.unit, unit_scope(&unit), max_log_lines, move |entry| {
+                    {
+                        let mut entries = entries.lock().unwrap();
+                        entries.push_back(entry);
+                        if entries.len() > max_log_lines {
+                            entries.pop_front();
+                        }
+                    }
+
+                    // a burst of messages only needs one redraw
+                    if pending.swap(true, Ordering::AcqRel) {
+                        return;
+                    }
+                    let pending = pending.clone();
+                    let entries = entries.clone();
+                    let _ = main_window_weak.upgrade_in_event_loop(move |main_window| {
+                        pending.store(false, Ordering::Release);
+                        render_log(&main_window, &entries);
+                    });
+                });
+
+            match follower {
+                Ok(follower) => {
+                    *log_follower_for_show.borrow_mut() = Some(follower);
+                    main_window.set_log_title(format!("Log of {}", unit.unit).into());
+                    main_window.set_log_filter("".into());
+                    main_window.set_log_paused(false);
+                    main_window.set_log_lines(Rc::new(slint::VecModel::default()).into());
+                    main_window.set_show_log(true);
+                }
+                Err(e) => notification::send(
+                    &format!("Failed to read the log of {}", unit.unit),
+                    &e.to_string(),
+                ),
+            }
+        });
+
+        let main_window_weak_for_log_refresh = main_window_weak.clone();
+        main_window.on_log_refresh(move || {
+            render_log(&main_window_weak_for_log_refresh.unwrap(), &log_entries);
+        });
+
+        main_window.on_log_closed(move || {
+            log_follower.borrow_mut().take();
+        });
+
+        // show what a unit pulls in and what pulls it in, e.g. to see what
+        // else stops along with it
+        let main_window_weak_for_dependencies = main_window_weak.clone();
+        main_window.on_show_unit_dependencies(move |unit| {
+            telemetry::record("show_unit_dependencies");
+            let main_window_weak = main_window_weak_for_dependencies.clone();
+            thread::spawn(move || {
+                let service = unit_service(&unit);
+                let dependencies = service
+                    .dependencies(false)
+                    .and_then(|wants| Ok((wants, service.dependencies(true)?)));
+
+                let _ =
+                    main_window_weak.upgrade_in_event_loop(move |main_window| match dependencies {
+                        Ok((wants, wanted_by)) => {
+                            let mut rows = dependency_rows("Pulls in", wants);
+                            rows.extend(dependency_rows("Pulled in by", wanted_by));
+                            show_info(
+                                &main_window,
+                                &format!("Dependencies of {}", unit.unit),
+                                rows,
+                            );
+                        }
+                        Err(e) => notification::send(
+                            &format!("Failed to read the dependencies of {}", unit.unit),
+                            &e.to_string(),
+                        ),
+                    });
+            });
+        });
+    }
+
+    // mount or unmount a peer's home directory, ssh may take a moment to connect
+    #[cfg(target_os = "linux")]
+    {
+        main_window.set_sshfs_available(sshfs::is_installed());
+        let main_window_weak_for_mount = main_window_weak.clone();
+        let sshfs_user = config.sshfs_user.clone();
+        main_window.on_toggle_mount(move |machine| {
+            telemetry::record("toggle_mount");
+            let main_window_weak = main_window_weak_for_mount.clone();
+            let sshfs_user = sshfs_user.clone();
+            thread::spawn(move || {
+                let hostname = machine.hostname.as_str();
+                if machine.mounted {
+                    if let Err(e) = sshfs::unmount(hostname) {
+                        notification::send(
+                            &format!("Failed to unmount {hostname}"),
+                            &e.to_string(),
+                        );
+                    }
+                } else {
+                    match sshfs::mount(hostname, &machine.ip, sshfs_user.as_deref()) {
+                        Ok(path) => notification::send(
+                            &format!("Mounted {hostname}"),
+                            &path.display().to_string(),
+                        ),
+                        Err(e) => notification::send(
+                            &format!("Failed to mount {hostname}"),
+                            &e.to_string(),
+                        ),
+                    }
+                }
+                let _ = main_window_weak.upgrade_in_event_loop(|main_window| {
+                    update_tailscale_state(&main_window);
+                });
+            });
+        });
+    }
+
+    // rename this device
+    let main_window_weak_for_rename = main_window_weak.clone();
+    main_window.on_rename_device(move |name| {
+        telemetry::record("rename_device");
+        let name = name.trim().to_string();
+        if name.is_empty() {
+            return;
+        }
+        let main_window_weak = main_window_weak_for_rename.clone();
+        thread::spawn(move || {
+            if let Err(e) = Tailscale::set_hostname(&name) {
+                notification::send("Failed to rename device", &e.to_string());
+            }
+            let _ = main_window_weak.upgrade_in_event_loop(|main_window| {
+                update_tailscale_state(&main_window);
+            });
+        });
+    });
+
+    // start on login
+    let autostart = autostart::platform();
+    main_window.set_autostart(autostart.is_enabled());
+    let main_window_weak_for_autostart = main_window_weak.clone();
+    main_window.on_toggle_autostart(move || {
+        telemetry::record("toggle_autostart");
+        let result = match autostart.is_enabled() {
+            true => autostart.disable(),
+            false => std::env::current_exe().and_then(|exe| autostart.enable(&exe)),
+        };
+
+        if let Err(e) = result {
+            notification::send("Failed to change autostart", &e.to_string());
+        }
+        let main_window = main_window_weak_for_autostart.unwrap();
+        main_window.set_autostart(autostart.is_enabled());
+    });
+
+    // locally recorded feature usage, only available when opted in
+    main_window.set_telemetry_enabled(telemetry::report().is_some());
+    let main_window_weak_for_usage = main_window_weak.clone();
+    main_window.on_show_usage_statistics(move || {
+        telemetry::record("show_usage_statistics");
+        let rows = telemetry::report()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(feature, count)| InfoRowData {
+                label: feature.into(),
+                value: count.to_string().into(),
+            })
+            .collect();
+        let main_window = main_window_weak_for_usage.unwrap();
+        show_info(&main_window, "Usage statistics", rows);
+    });
+
+    // upload diagnostics and hand out the identifier
+    main_window.on_create_bugreport(move || {
+        telemetry::record("create_bugreport");
+        thread::spawn(|| match Tailscale::bugreport() {
+            Ok(id) => {
+                let copied = cli_clipboard::set_contents(id.clone()).is_ok();
+                let body = match copied {
+                    true => format!("{id}\nCopied to the clipboard."),
+                    false => id,
+                };
+                notification::send("Bug report created", &body);
+            }
+            Err(e) => notification::send("Failed to create bug report", &e.to_string()),
+        });
+    });
+
+    // run the configured update command, it may download for a while
+    let update_command = config.update_command.clone();
+    main_window.set_can_update(update_command.is_some());
+    let main_window_weak_for_update = main_window_weak.clone();
+    main_window.on_run_update(move || {
+        telemetry::record("run_update");
+        let Some((program, args)) = update_command.as_ref().and_then(|c| c.split_first()) else {
+            return;
+        };
+
+        let mut command = Command::new(program);
+        command.args(args);
+        let main_window_weak = main_window_weak_for_update.clone();
+        thread::spawn(move || {
+            match process::output_with_timeout(&mut command, None) {
+                Ok(output) if output.status.success() => {
+                    notification::send("Tailscale updated", "")
+                }
+                Ok(output) => notification::send(
+                    "Tailscale update failed",
+                    &String::from_utf8_lossy(&output.stderr),
+                ),
+                Err(e) => notification::send("Tailscale update failed", &e.to_string()),
+            }
+            let _ = main_window_weak.upgrade_in_event_loop(|main_window| {
+                update_tailscale_state(&main_window);
+            });
+        });
+    });
+
+    main_window.on_tour_finished(|| {
+        telemetry::record("tour_finished");
+        tour::mark_done();
+    });
+
+    // pause what polls in the background until resumed, not kept across restarts
+    let main_window_weak_for_pause = main_window_weak.clone();
+    main_window.on_set_paused(move |integration, paused| {
+        telemetry::record("set_paused");
+        let main_window = main_window_weak_for_pause.unwrap();
+        match integration.as_str() {
+            #[cfg(target_os = "linux")]
+            "services" => {
+                SERVICES_PAUSED.store(paused, Ordering::Relaxed);
+                main_window.set_services_paused(paused);
+                if !paused {
+                    refresh_services(&main_window);
+                }
+            }
+            "monitor" => {
+                monitor::set_paused(paused);
+                main_window.set_monitor_paused(paused);
+            }
+            #[cfg(feature = "schedules")]
+            "schedules" => {
+                schedule::set_paused(paused);
+                main_window.set_schedules_paused(paused);
+            }
+            _ => {}
+        }
+    });
+
+    main_window.on_quit(|| {
+        let _ = slint::quit_event_loop();
+    });
+
+    // quitting always stops what the config asks for, this also stops the rest
+    let stop_services = Rc::new(Cell::new(false));
+    let stop_services_for_quit = stop_services.clone();
+    main_window.on_quit_and_stop_services(move || {
+        telemetry::record("quit_and_stop_services");
+        stop_services_for_quit.set(true);
+        let _ = slint::quit_event_loop();
+    });
+
+    // tailscaled reports nothing while the node key approaches its expiry
+    let key_expiry_timer = slint::Timer::default();
+    let main_window_weak_for_key_expiry = main_window_weak.clone();
+    key_expiry_timer.start(slint::TimerMode::Repeated, KEY_EXPIRY_INTERVAL, move || {
+        let main_window = main_window_weak_for_key_expiry.unwrap();
+        update_tailscale_state(&main_window);
+    });
+
+    // with low_memory, one coarse check instead of the IPN bus watch and the
+    // Services timer
+    let low_memory_timer = slint::Timer::default();
+    if config.low_memory {
+        let main_window_weak_for_refresh = main_window_weak.clone();
+        low_memory_timer.start(slint::TimerMode::Repeated, LOW_MEMORY_INTERVAL, move || {
+            let main_window = main_window_weak_for_refresh.unwrap();
+            update_tailscale_state(&main_window);
+            #[cfg(target_os = "linux")]
+            refresh_services(&main_window);
+        });
+    }
+
+    // report readiness to systemd once the event loop runs, and keep pinging
+    // its watchdog from the loop
+    #[cfg(target_os = "linux")]
+    let watchdog_timer = slint::Timer::default();
+    #[cfg(target_os = "linux")]
+    {
+        slint::Timer::single_shot(std::time::Duration::ZERO, sd_notify::ready);
+        if let Some(mut interval) = sd_notify::watchdog_interval() {
+            // the unit may predate a change of the command timeout
+            let command_timeout = std::time::Duration::from_secs(config.command_timeout_secs);
+            if let Some(timeout) = sd_notify::watchdog_timeout(command_timeout) {
+                sd_notify::set_watchdog_timeout(timeout);
+                interval = timeout / 2;
+            }
+            watchdog_timer.start(slint::TimerMode::Repeated, interval, sd_notify::watchdog);
+        }
+    }
+
+    main_window.run().unwrap();
+    process::kill_all();
+    let (disconnect, stop_daemon) = match stop_services.get() {
+        true => (true, true),
+        false => (config.quit_disconnects, config.quit_stops_daemon),
+    };
+    stop_services_on_quit(&main_window, disconnect, stop_daemon);
+    #[cfg(all(unix, feature = "scripting"))]
+    fifo::remove();
+}
+
+/// Disconnects and stops tailscaled as asked for when tailslint exits, notifying
+/// about failures since the window is gone by now.
+fn stop_services_on_quit(main_window: &MainWindow, disconnect: bool, stop_daemon: bool) {
+    if disconnect
+        && Tailscale::state() == TailscaleState::Running
+        && let Err(e) = Tailscale::down()
+    {
+        notification::send("Failed to disconnect", &e.to_string());
+    }
+
+    #[cfg(target_os = "linux")]
+    if stop_daemon
+        && main_window.get_service_available()
+        && let Err(e) = service::Service::tailscaled().stop()
+    {
+        notification::send("Failed to stop tailscaled", &e.to_string());
+    }
+    #[cfg(not(target_os = "linux"))]
+    let _ = (main_window, stop_daemon);
+}
+
+/// What the Services menu shows about a unit, read off the UI thread.
+#[cfg(target_os = "linux")]
+struct UnitSnapshot {
+    unit: service::WatchedUnit,
+    status: Result<service::UnitStatus, service::ServiceError>,
+    /// The timer unit and when it runs next.
+    timer: Option<(String, String)>,
+}
+
+#[cfg(target_os = "linux")]
+static SERVICES_REFRESH: Refresh = Refresh::new();
+
+/// Reads the state of `units` on a worker thread into the Services menu.
+#[cfg(target_os = "linux")]
+fn update_services(main_window: &MainWindow, units: &[service::WatchedUnit]) {
+    if SERVICES_PAUSED.load(Ordering::Relaxed) {
+        return;
+    }
+    let units = units.to_vec();
+    let main_window_weak = main_window.as_weak();
+    SERVICES_REFRESH.spawn(move || {
+        let snapshots: Vec<UnitSnapshot> = units.iter().cloned().map(read_unit).collect();
+        let _ = main_window_weak.upgrade_in_event_loop(move |main_window| {
+            show_services(&main_window, snapshots);
+        });
+    });
+}
+
+#[cfg(target_os = "linux")]
+fn read_unit(unit: service::WatchedUnit) -> UnitSnapshot {
+    let service = service::Service::with_scope(&unit.unit, unit.scope);
+    let timer = service.timer().map(|timer| {
+        let state = match timer.status() {
+            Ok(status) => match status.next_elapse {
+                Some(next) => format!("next run {}", format::relative(next)),
+                None if status.is_active() => "not scheduled".to_string(),
+                None => "stopped".to_string(),
+            },
+            Err(e) => e.to_string(),
+        };
+        (timer.unit().to_string(), state)
+    });
+    UnitSnapshot {
+        status: service.status(),
+        timer,
+        unit,
+    }
+}
+
+/// Shows the units read by `update_services()`. Units being started or
+/// stopped stay marked busy. Units that failed since the last update are
+/// notified about, with their last log lines and a button to restart them.
+#[cfg(target_os = "linux")]
+fn show_services(main_window: &MainWindow, snapshots: Vec<UnitSnapshot>) {
+    let previous = main_window.get_services();
+    let services: Vec<ServiceData> = snapshots
+        .into_iter()
+        .map(
+            |UnitSnapshot {
+                 unit,
+                 status,
+                 timer,
+             }| {
+                let user = unit.scope == service::Scope::User;
+                let old = previous
+                    .iter()
+                    .find(|old| old.unit == unit.unit && old.user == user);
+                let (timer, timer_state) = timer.unwrap_or_default();
+
+                let failed = status.as_ref().is_ok_and(|status| status.is_failed());
+                if failed && old.as_ref().is_some_and(|old| !old.failed) {
+                    notify_unit_failed(service::Service::with_scope(&unit.unit, unit.scope));
+                }
+
+                ServiceData {
+                    failed,
+                    systemd: service::init_system() == service::InitSystem::Systemd,
+                    timer: timer.into(),
+                    timer_state: timer_state.into(),
+                    unit: unit.unit.as_str().into(),
+                    user,
+                    active: status.as_ref().is_ok_and(|status| status.is_active()),
+                    enabled: status.as_ref().is_ok_and(|status| status.is_enabled()),
+                    can_enable: status.as_ref().is_ok_and(|status| status.can_enable()),
+                    masked: status.as_ref().is_ok_and(|status| status.is_masked()),
+                    state: match &status {
+                        Ok(status) => describe_unit(status),
+                        Err(e) => e.to_string(),
+                    }
+                    .into(),
+                    busy: old.is_some_and(|old| old.busy),
+                }
+            },
+        )
+        .collect();
+    main_window.set_services(Rc::new(slint::VecModel::from(services)).into());
+}
+
+/// Notifies about a failed unit, reading its journal first off the UI thread.
+#[cfg(target_os = "linux")]
+fn notify_unit_failed(service: service::Service) {
+    thread::spawn(move || {
+        let logs = service.recent_logs(5).unwrap_or_default();
+        notification::send_with_action(
+            &format!("{} failed", service.unit()),
+            logs.trim(),
+            "Restart",
+            move || {
+                if let Err(e) = service.restart() {
+                    notification::send(
+                        &format!("Failed to restart {}", service.unit()),
+                        &e.to_string(),
+                    );
+                }
+            },
+        );
+    });
+}
+
+/// Summarizes a unit's status, e.g. "running for 2h 13m, 0 restarts, PID 812,
+/// 45.3 MiB (peak 60.1 MiB), 3 minutes CPU".
+#[cfg(target_os = "linux")]
+fn describe_unit(status: &service::UnitStatus) -> String {
+    let mut description = match status.active_since {
+        Some(since) if status.is_active() => format!(
+            "{} for {}",
+            status.sub_state,
+            format::uptime(chrono::Utc::now() - since)
+        ),
+        _ => format!("{} ({})", status.active_state, status.sub_state),
+    };
+    if status.is_active() || status.restarts != 0 {
+        let restarts = match status.restarts {
+            1 => "1 restart".to_string(),
+            n => format!("{n} restarts"),
+        };
+        description.push_str(&format!(", {restarts}"));
+    }
+    if status.main_pid != 0 {
+        description.push_str(&format!(", PID {}", status.main_pid));
+    }
+    if let Some(bytes) = status.memory_current {
+        description.push_str(&format!(", {}", format::bytes(bytes)));
+        if let Some(peak) = status.memory_peak {
+            description.push_str(&format!(" (peak {})", format::bytes(peak)));
+        }
+    }
+    if let Some(cpu) = status.cpu_usage
+        && let Ok(cpu) = chrono::TimeDelta::from_std(cpu)
+    {
+        description.push_str(&format!(", {} CPU", format::duration(cpu)));
+    }
+    description
+}
+
+/// Shows the journal messages matching the search in the log viewer, newest
+/// first, unless it is paused.
+#[cfg(target_os = "linux")]
+fn render_log(main_window: &MainWindow, entries: &Mutex<VecDeque<journal::Entry>>) {
+    if main_window.get_log_paused() {
+        return;
+    }
+
+    let filter = main_window.get_log_filter().to_lowercase();
+    let lines: Vec<LogLineData> = entries
+        .lock()
+        .unwrap()
+        .iter()
+        .rev()
+        .filter(|entry| filter.is_empty() || entry.message.to_lowercase().contains(&filter))
+        .map(|entry| LogLineData {
+            time: entry
+                .time
+                .map(|time| time.format("%H:%M:%S").to_string())
+                .unwrap_or_default()
+                .into(),
+            message: entry.message.as_str().into(),
+            priority: entry.priority.into(),
+        })
+        .collect();
+    main_window.set_log_lines(Rc::new(slint::VecModel::from(lines)).into());
+}
+
+/// Rows for a dependency tree, indented by depth and labelled with `label` on
+/// the first row.
+#[cfg(target_os = "linux")]
+fn dependency_rows(label: &str, tree: Vec<(usize, String)>) -> Vec<InfoRowData> {
+    if tree.is_empty() {
+        return vec![InfoRowData {
+            label: label.into(),
+            value: "nothing".into(),
+        }];
+    }
+
+    tree.into_iter()
+        .enumerate()
+        .map(|(i, (depth, unit))| InfoRowData {
+            label: if i == 0 { label.into() } else { "".into() },
+            // non-breaking spaces, as leading spaces would be collapsed when wrapping
+            value: format!("{}{unit}", "\u{a0}\u{a0}\u{a0}".repeat(depth)).into(),
+        })
+        .collect()
+}
+
+/// Reads the state of the units already in the Services menu again.
+#[cfg(target_os = "linux")]
+fn refresh_services(main_window: &MainWindow) {
+    let units: Vec<service::WatchedUnit> = main_window
+        .get_services()
+        .iter()
+        .map(|service| service::WatchedUnit {
+            scope: unit_scope(&service),
+            unit: service.unit.into(),
+        })
+        .collect();
+    update_services(main_window, &units);
+}
+
+#[cfg(target_os = "linux")]
+fn unit_scope(unit: &ServiceData) -> service::Scope {
+    match unit.user {
+        true => service::Scope::User,
+        false => service::Scope::System,
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn unit_service(unit: &ServiceData) -> service::Service {
+    service::Service::with_scope(&unit.unit, unit_scope(unit))
+}
+
+#[cfg(target_os = "linux")]
+fn set_service_busy(main_window: &MainWindow, unit: &ServiceData, busy: bool) {
+    let services = main_window.get_services();
+    for (i, mut service) in services.iter().enumerate() {
+        if service.unit == unit.unit && service.user == unit.user {
+            service.busy = busy;
+            services.set_row_data(i, service);
+        }
+    }
+}
+
+/// Everything the window shows about tailscaled, read off the UI thread.
+struct TailscaleSnapshot {
+    #[cfg(target_os = "linux")]
+    service_active: Option<bool>,
+    status: TailscaleStatus,
+    serve_handlers: Vec<ServeHandlerData>,
+    drive_shares: Vec<DriveShareData>,
+    profiles: Vec<ProfileData>,
+    #[cfg(target_os = "linux")]
+    mounted: Option<Vec<String>>,
+}
+
+/// A read done on a worker thread, so a wedged daemon can't freeze the window.
+/// Whether one is running, and whether it has to run once more because
+/// something changed meanwhile.
+struct Refresh {
+    running: AtomicBool,
+    again: AtomicBool,
+}
+
+impl Refresh {
+    const fn new() -> Self {
+        Refresh {
+            running: AtomicBool::new(false),
+            again: AtomicBool::new(false),
+        }
+    }
+
+    /// Runs `read` on a worker thread. Calls while it runs are folded into one
+    /// more run after it.
+    fn spawn(&'static self, read: impl Fn() + Send + 'static) {
+        if self.running.swap(true, Ordering::AcqRel) {
+            self.again.store(true, Ordering::Release);
+            return;
+        }
+
+        thread::spawn(move || {
+            loop {
+                self.again.store(false, Ordering::Release);
+                read();
+
+                if self.again.load(Ordering::Acquire) {
+                    continue;
+                }
+                self.running.store(false, Ordering::Release);
+                // a call may have come in between the check and the store
+                if !self.again.load(Ordering::Acquire) || self.running.swap(true, Ordering::AcqRel)
+                {
+                    return;
+                }
+            }
+        });
+    }
+}
+
+static TAILSCALE_REFRESH: Refresh = Refresh::new();
+
+/// Reads tailscaled's state on a worker thread and shows it once read.
+fn update_tailscale_state(main_window: &MainWindow) {
+    #[cfg(target_os = "linux")]
+    let service_available = main_window.get_service_available();
+    #[cfg(target_os = "linux")]
+    let sshfs_available = main_window.get_sshfs_available();
+    let main_window_weak = main_window.as_weak();
+    TAILSCALE_REFRESH.spawn(move || {
+        let snapshot = read_tailscale_state(
+            #[cfg(target_os = "linux")]
+            service_available,
+            #[cfg(target_os = "linux")]
+            sshfs_available,
+        );
+        let _ = main_window_weak.upgrade_in_event_loop(move |main_window| {
+            show_tailscale_state(&main_window, snapshot);
+        });
+    });
+}
+
+fn read_tailscale_state(
+    #[cfg(target_os = "linux")] service_available: bool,
+    #[cfg(target_os = "linux")] sshfs_available: bool,
+) -> TailscaleSnapshot {
+    let status = match Tailscale::status() {
+        Ok(status) => status,
+        Err(TailscaleError::Timeout) => TailscaleStatus {
+            state: TailscaleState::NotResponding,
+            ..Default::default()
+        },
+        Err(_) => TailscaleStatus::default(),
+    };
+
+    // only a running node has handlers and shares, and without a responding
+    // daemon there are no profiles to switch between either
+    let running = status.state == TailscaleState::Running;
+    let up_or_down = running || status.state == TailscaleState::Stopped;
+    TailscaleSnapshot {
+        #[cfg(target_os = "linux")]
+        service_active: service_available.then(|| service::Service::tailscaled().is_active()),
+        serve_handlers: match running {
+            true => Tailscale::serve_status().unwrap_or_default(),
+            false => vec![],
+        },
+        drive_shares: match running {
+            true => Tailscale::drive_shares().unwrap_or_default(),
+            false => vec![],
+        },
+        profiles: match up_or_down {
+            true => Tailscale::profiles().unwrap_or_default(),
+            false => vec![],
+        },
+        #[cfg(target_os = "linux")]
+        mounted: sshfs_available.then(sshfs::mounted),
+        status,
+    }
+}
+
+fn show_tailscale_state(main_window: &MainWindow, snapshot: TailscaleSnapshot) {
+    #[cfg(target_os = "linux")]
+    if let Some(active) = snapshot.service_active {
+        main_window.set_service_active(active);
+    }
+
+    // apart from the state and version, nothing is shown while disconnected
+    let status = snapshot.status;
+    let enabled = status.state == TailscaleState::Running;
+    let state = connection_state(status.state);
+    if state == ConnectionState::NeedsMachineAuth && main_window.get_state() != state {
+        notification::send_state_change(
+            "Waiting for admin approval",
+            "This device has to be approved in the admin console before it can connect.",
+        );
+    }
+    main_window.set_state(state);
+    main_window.set_version(status.version.as_str().into());
+    #[cfg(target_os = "linux")]
+    sd_notify::status(&describe_state(&status));
+    let status = match enabled {
+        true => status,
+        false => TailscaleStatus::default(),
+    };
+
+    if let Some(expiry) = status.key_expiry {
+        warn_about_key_expiry(expiry);
+    }
+    let key_expiry = status
+        .key_expiry
+        .map(describe_key_expiry)
+        .unwrap_or_default();
+    main_window.set_key_expiry(key_expiry.into());
+
+    let seeded = !enabled || HEALTH_WARNINGS_SEEDED.swap(true, Ordering::Relaxed);
+    if seeded && NOTIFY_HEALTH_WARNINGS.load(Ordering::Relaxed) {
+        let known: Vec<SharedString> = main_window.get_health_warnings().iter().collect();
+        for warning in &status.health {
+            if !known.iter().any(|known| known.as_str() == warning) {
+                notification::send_state_change("Tailscale health warning", warning);
+            }
+        }
+    }
+    let warnings: Vec<SharedString> = status.health.iter().map(Into::into).collect();
+    main_window.set_health_warnings(Rc::new(slint::VecModel::from(warnings)).into());
+
+    let serve_model = Rc::new(slint::VecModel::from(snapshot.serve_handlers));
+    main_window.set_serve_handlers(serve_model.into());
+
+    let drive_model = Rc::new(slint::VecModel::from(snapshot.drive_shares));
+    main_window.set_drive_shares(drive_model.into());
+
+    let available_update = status.available_update.unwrap_or_default();
+    main_window.set_available_update(available_update.into());
+    main_window.set_local_node(status.self_node);
+
+    let mut machines = status.peers;
+    #[cfg(target_os = "linux")]
+    if let Some(mounted) = snapshot.mounted {
+        for machine in &mut machines {
+            machine.mounted = mounted
+                .iter()
+                .any(|host| host.as_str() == machine.hostname.as_str());
+        }
+    }
+
+    let machine_model = Rc::new(slint::VecModel::from(machines));
+    main_window.set_machines(machine_model.clone().into());
+
+    let profile_model = Rc::new(slint::VecModel::from(snapshot.profiles));
+    main_window.set_profiles(profile_model.into());
+}
+
+/// Summarizes the connection, e.g. "Connected, 3 of 5 machines online".
+#[cfg(target_os = "linux")]
+fn describe_state(status: &TailscaleStatus) -> String {
+    let state = match status.state {
+        TailscaleState::Running => "Connected",
+        TailscaleState::Stopped => "Disconnected",
+        TailscaleState::NeedsLogin => "Login required",
+        TailscaleState::NeedsMachineAuth => "Waiting for admin approval",
+        TailscaleState::DaemonDown => "tailscaled not running",
+        TailscaleState::NotResponding => "tailscaled not responding",
+        TailscaleState::Unknown => "tailscaled in an unknown state",
+    };
+    if status.state != TailscaleState::Running {
+        return state.to_string();
+    }
+
+    let online = status.peers.iter().filter(|peer| peer.online).count();
+    format!(
+        "{state}, {online} of {} machines online",
+        status.peers.len()
+    )
+}
+
+fn connection_state(state: TailscaleState) -> ConnectionState {
+    match state {
+        TailscaleState::Running => ConnectionState::Running,
+        TailscaleState::Stopped => ConnectionState::Stopped,
+        TailscaleState::NeedsLogin => ConnectionState::NeedsLogin,
+        TailscaleState::NeedsMachineAuth => ConnectionState::NeedsMachineAuth,
+        TailscaleState::DaemonDown => ConnectionState::DaemonDown,
+        TailscaleState::NotResponding => ConnectionState::NotResponding,
+        TailscaleState::Unknown => ConnectionState::Unknown,
+    }
+}
+
+/// Shows a list of label/value rows in the info dialog.
+fn show_info(main_window: &MainWindow, title: &str, rows: Vec<InfoRowData>) {
+    let rows = Rc::new(slint::VecModel::from(rows));
+    main_window.set_info_title(title.into());
+    main_window.set_info_rows(rows.into());
+    main_window.set_show_info(true);
+}
+
+/// Posts a notification if the node key expires within the configured days,
+/// once per key.
+fn warn_about_key_expiry(expiry: DateTime<Utc>) {
+    let warning_days = KEY_EXPIRY_WARNING_DAYS.load(Ordering::Relaxed);
+    if (expiry - Utc::now()).num_days() >= warning_days {
+        return;
+    }
+
+    let mut warned = WARNED_KEY_EXPIRY.lock().unwrap();
+    if warned.replace(expiry) != Some(expiry) {
+        let body = format!(
+            "{}. Re-authenticate this device to stay connected.",
+            describe_key_expiry(expiry)
+        );
+        notification::send("Tailscale key expiring", &body);
+    }
+}
+
+/// Formats the remaining key lifetime, e.g. "Key expires in 12 days".
+fn describe_key_expiry(expiry: DateTime<Utc>) -> String {
+    match expiry > Utc::now() {
+        true => format!("Key expires {}", format::relative(expiry)),
+        false => format!("Key expired {}", format::relative(expiry)),
+    }
+}
+
+/// Derives a Taildrive share name from a directory, e.g. "/home/me/My Docs" -> "my docs".
+/// Share names may only contain lowercase letters, digits, `_`, `-`, `(`, `)` and spaces.
+fn drive_share_name(path: &str) -> String {
+    let base = std::path::Path::new(path)
+        .file_name()
+        .map(|name| name.to_string_lossy().to_lowercase())
+        .unwrap_or_else(|| "share".into());
+
+    base.chars()
+        .map(|c| match c {
+            'a'..='z' | '0'..='9' | '_' | '-' | '(' | ')' | ' ' => c,
+            _ => '_',
+        })
+        .collect()
 }
@@ -0,0 +1,74 @@
+//! Latency monitoring of one peer, e.g. a home server. A background thread
+//! pings it every 30 seconds and shows the result in the Tailscale menu. A
+//! notification is sent when the peer gets slower than the threshold or stops
+//! answering, once each time it does. Pinging can be paused from the Settings
+//! menu.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::Duration;
+
+use crate::tailscale::Tailscale;
+use crate::{MainWindow, notification};
+
+/// How long to wait between pings.
+const INTERVAL: Duration = Duration::from_secs(30);
+
+static PAUSED: AtomicBool = AtomicBool::new(false);
+
+/// Stops or resumes pinging, from the next ping on.
+pub fn set_paused(paused: bool) {
+    PAUSED.store(paused, Ordering::Relaxed);
+}
+
+/// How a ping went, compared to the threshold.
+#[derive(PartialEq)]
+enum Outcome {
+    Fast,
+    Slow,
+    Unreachable,
+}
+
+/// Starts pinging `peer`, alerting above `threshold_ms`.
+pub fn start(main_window_weak: slint::Weak<MainWindow>, peer: String, threshold_ms: u64) {
+    thread::spawn(move || {
+        let mut last = Outcome::Fast;
+        loop {
+            if PAUSED.load(Ordering::Relaxed) {
+                let label = format!("{peer}  paused");
+                let _ = main_window_weak.upgrade_in_event_loop(move |main_window| {
+                    main_window.set_monitor_status(label.into());
+                });
+                thread::sleep(INTERVAL);
+                continue;
+            }
+
+            let (outcome, status) = match Tailscale::ping(&peer) {
+                Ok(ms) if ms > threshold_ms as f64 => (Outcome::Slow, format!("{ms:.0} ms")),
+                Ok(ms) => (Outcome::Fast, format!("{ms:.0} ms")),
+                Err(_) => (Outcome::Unreachable, "no reply".to_string()),
+            };
+
+            if outcome != last {
+                match outcome {
+                    Outcome::Slow => notification::send_state_change(
+                        &format!("{peer} is slow"),
+                        &format!("A ping took {status}, more than {threshold_ms} ms."),
+                    ),
+                    Outcome::Unreachable => notification::send_state_change(
+                        &format!("{peer} is not responding"),
+                        "A ping got no reply within five seconds.",
+                    ),
+                    Outcome::Fast => {}
+                }
+            }
+            last = outcome;
+
+            let label = format!("{peer}  {status}");
+            let _ = main_window_weak.upgrade_in_event_loop(move |main_window| {
+                main_window.set_monitor_status(label.into());
+            });
+            thread::sleep(INTERVAL);
+        }
+    });
+}
@@ -0,0 +1,84 @@
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime};
+
+/// How often the clocks are compared to notice the system waking up.
+const WAKE_CHECK: Duration = Duration::from_secs(5);
+
+/// When the state last started settling, at launch or waking from suspend,
+/// and for how long `send_state_change()` holds notifications back after.
+static SETTLING: Mutex<Option<(Instant, Duration)>> = Mutex::new(None);
+
+/// Posts a desktop notification. Failures are ignored, a missing notification
+/// daemon should never get in the way of the UI.
+pub fn send(summary: &str, body: &str) {
+    let _ = notify_rust::Notification::new()
+        .appname("tailslint")
+        .summary(summary)
+        .body(body)
+        .show();
+}
+
+/// Starts holding back `send_state_change()` notifications for `window`, now
+/// and every time the system wakes from suspend.
+pub fn settle(window: Duration) {
+    if window.is_zero() {
+        return;
+    }
+    *SETTLING.lock().unwrap() = Some((Instant::now(), window));
+
+    std::thread::spawn(move || {
+        let mut last = (Instant::now(), SystemTime::now());
+        loop {
+            std::thread::sleep(WAKE_CHECK);
+            let now = (Instant::now(), SystemTime::now());
+
+            // the monotonic clock stands still while suspended, the wall clock doesn't
+            let wall = now.1.duration_since(last.1).unwrap_or_default();
+            if wall.saturating_sub(now.0 - last.0) > WAKE_CHECK {
+                *SETTLING.lock().unwrap() = Some((now.0, window));
+            }
+            last = now;
+        }
+    });
+}
+
+/// Posts a notification about something that changed on its own rather than
+/// after a click, unless the state is still settling after launch or waking
+/// up. Those would be about things that were already true.
+pub fn send_state_change(summary: &str, body: &str) {
+    let settling = SETTLING
+        .lock()
+        .unwrap()
+        .is_some_and(|(since, window)| since.elapsed() < window);
+    if !settling {
+        send(summary, body);
+    }
+}
+
+/// Posts a desktop notification with a button, running `on_action` on a
+/// background thread should it be clicked.
+#[cfg(target_os = "linux")]
+pub fn send_with_action(
+    summary: &str,
+    body: &str,
+    label: &str,
+    on_action: impl FnOnce() + Send + 'static,
+) {
+    let notification = notify_rust::Notification::new()
+        .appname("tailslint")
+        .summary(summary)
+        .body(body)
+        .action("default", label)
+        .finalize();
+
+    // waiting for the click blocks until the notification is closed
+    std::thread::spawn(move || {
+        if let Ok(handle) = notification.show() {
+            handle.wait_for_action(|action| {
+                if action == "default" {
+                    on_action();
+                }
+            });
+        }
+    });
+}
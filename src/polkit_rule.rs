@@ -0,0 +1,63 @@
+//! The `install-polkit-rule` and `uninstall-polkit-rule` subcommands, for
+//! starting and stopping units without authenticating every time. The rule
+//! only covers starting, stopping and restarting the listed units, for the
+//! user who ran the command through sudo. Both have to run as root.
+
+use std::io;
+use std::path::Path;
+
+/// Where polkit picks up local rules.
+const RULE_PATH: &str = "/etc/polkit-1/rules.d/50-tailslint.rules";
+
+/// The user the rule is for, the one who invoked sudo.
+fn user() -> io::Result<String> {
+    std::env::var("SUDO_USER")
+        .or_else(|_| std::env::var("USER"))
+        .map_err(|_| io::Error::other("can't tell which user the rule is for"))
+}
+
+/// The rule allowing `user` to start, stop and restart `units`. Names are
+/// written as JSON strings, which are valid JavaScript string literals.
+fn rule(user: &str, units: &[String]) -> String {
+    let quote = |s: &str| serde_json::to_string(s).unwrap_or_default();
+    let units: Vec<String> = units.iter().map(|unit| quote(unit)).collect();
+
+    format!(
+        "// Written by `tailslint install-polkit-rule`, lets {user} start and stop\n\
+         // the units below without authenticating.\n\
+         polkit.addRule(function(action, subject) {{\n    \
+             if (action.id == \"org.freedesktop.systemd1.manage-units\" &&\n        \
+                 subject.user == {user_literal} &&\n        \
+                 [{units}].indexOf(action.lookup(\"unit\")) >= 0 &&\n        \
+                 [\"start\", \"stop\", \"restart\"].indexOf(action.lookup(\"verb\")) >= 0) {{\n        \
+                 return polkit.Result.YES;\n    \
+             }}\n\
+         }});\n",
+        user_literal = quote(user),
+        units = units.join(", "),
+    )
+}
+
+/// Writes the rule for `units`, tailscaled's unit if none are given.
+pub fn install(units: &[String]) -> io::Result<&'static Path> {
+    let units = match units.is_empty() {
+        true => vec!["tailscaled.service".to_string()],
+        false => units.to_vec(),
+    };
+    if let Some(unit) = units.iter().find(|unit| !unit.contains('.')) {
+        return Err(io::Error::other(format!(
+            "{unit} is not a unit name, e.g. {unit}.service"
+        )));
+    }
+
+    std::fs::write(RULE_PATH, rule(&user()?, &units))?;
+    Ok(Path::new(RULE_PATH))
+}
+
+/// Removes the rule, if it was installed.
+pub fn uninstall() -> io::Result<()> {
+    match std::fs::remove_file(RULE_PATH) {
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+        result => result,
+    }
+}
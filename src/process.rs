@@ -0,0 +1,459 @@
+//! The registry of child processes tailslint starts.
+//!
+//! Commands are waited for through `output()`, followed as they print through
+//! `spawn_streaming()` and desktop handlers that are left running through
+//! `spawn_detached()`. Either way the child stays registered
+//! until it exited and was reaped, so none linger as zombies, and `kill_all()`
+//! stops the commands still being waited for when tailslint quits or panics.
+//!
+//! Captured output is capped at `MAX_OUTPUT` bytes per stream and stripped of
+//! terminal escape sequences and control characters before anyone parses it.
+//! Commands taking longer than the timeout set with `set_timeout()` are killed,
+//! so a wedged daemon can't hang the window.
+//!
+//! The `tailscale` and `systemctl` wrappers go through `run()`, whose
+//! `CommandRunner` can be swapped out to answer with canned or simulated output.
+
+use std::io::{self, Read};
+use std::process::{Child, ChildStdout, Command, Output, Stdio};
+use std::sync::mpsc;
+use std::sync::{Mutex, RwLock, TryLockError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+struct Entry {
+    child: Child,
+    /// Whether the child is killed when tailslint exits, only false for
+    /// detached handlers that are meant to outlive it.
+    kill_on_exit: bool,
+}
+
+static CHILDREN: Mutex<Vec<Entry>> = Mutex::new(Vec::new());
+
+/// The most bytes kept of a command's stdout or stderr, far above what even a
+/// large tailnet's `tailscale status --json` prints.
+pub const MAX_OUTPUT: u64 = 8 * 1024 * 1024;
+
+/// Appended to output that was cut off at `MAX_OUTPUT`.
+const TRUNCATED: &[u8] = b"\n[output truncated]\n";
+
+/// How long `output()` waits for a command, `None` to wait forever.
+static TIMEOUT: Mutex<Option<Duration>> = Mutex::new(Some(Duration::from_secs(30)));
+
+/// Sets how long `output()` waits before killing a command, zero to wait forever.
+pub fn set_timeout(timeout: Duration) {
+    *TIMEOUT.lock().unwrap() = Some(timeout).filter(|timeout| !timeout.is_zero());
+}
+
+/// The captured result of a command.
+pub struct CommandOutput {
+    pub success: bool,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+/// Answers the commands the wrappers run.
+pub trait CommandRunner: Send + Sync {
+    fn run(&self, program: &str, args: &[&str]) -> io::Result<CommandOutput>;
+}
+
+/// Runs commands as child processes, through `output()`. The C locale is forced
+/// since the wrappers parse the output, which may otherwise be translated.
+pub struct SystemRunner;
+
+impl CommandRunner for SystemRunner {
+    fn run(&self, program: &str, args: &[&str]) -> io::Result<CommandOutput> {
+        let output = output(
+            Command::new(program)
+                .args(args)
+                .env("LC_ALL", "C")
+                .env("LANG", "C"),
+        )?;
+        Ok(CommandOutput {
+            success: output.status.success(),
+            stdout: String::from_utf8_lossy(&output.stdout).to_string(),
+            stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+        })
+    }
+}
+
+/// The runner behind `run()`, `SystemRunner` unless replaced.
+static RUNNER: RwLock<Option<Box<dyn CommandRunner>>> = RwLock::new(None);
+
+/// Replaces the runner behind `run()`.
+pub fn set_runner(runner: impl CommandRunner + 'static) {
+    *RUNNER.write().unwrap() = Some(Box::new(runner));
+}
+
+/// Runs `program` with `args` through the current runner.
+pub fn run(program: &str, args: &[&str]) -> io::Result<CommandOutput> {
+    match RUNNER.read().unwrap().as_ref() {
+        Some(runner) => runner.run(program, args),
+        None => SystemRunner.run(program, args),
+    }
+}
+
+/// Runs `command` to completion like `Command::output`, killing it should
+/// tailslint exit first or it exceed the timeout, which fails with
+/// `io::ErrorKind::TimedOut`. The output is capped and sanitized, see above.
+pub fn output(command: &mut Command) -> io::Result<Output> {
+    let timeout = *TIMEOUT.lock().unwrap();
+    output_with_timeout(command, timeout)
+}
+
+/// Like `output()`, with its own timeout instead of the one set globally. For
+/// commands that are expected to take long, such as updates.
+pub fn output_with_timeout(command: &mut Command, timeout: Option<Duration>) -> io::Result<Output> {
+    reap();
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    let id = child.id();
+    let stdout_pipe = child.stdout.take();
+    let stderr_pipe = child.stderr.take();
+    register(child, true);
+
+    // both pipes are drained at once, a child filling one while we block on the
+    // other would otherwise never exit. The pipes close when the child exits.
+    let (stdout_sender, stdout_receiver) = mpsc::channel();
+    let (stderr_sender, stderr_receiver) = mpsc::channel();
+    thread::spawn(move || stdout_sender.send(read_all(stdout_pipe)));
+    thread::spawn(move || stderr_sender.send(read_all(stderr_pipe)));
+
+    let deadline = timeout.map(|timeout| Instant::now() + timeout);
+    let (Some(stdout), Some(stderr)) = (
+        receive(&stdout_receiver, deadline),
+        receive(&stderr_receiver, deadline),
+    ) else {
+        if let Some(mut child) = take(id) {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+        let program = command.get_program().to_string_lossy();
+        let timeout = timeout.unwrap_or_default().as_secs();
+        return Err(io::Error::new(
+            io::ErrorKind::TimedOut,
+            format!("{program} did not finish within {timeout}s"),
+        ));
+    };
+
+    let mut child = take(id).ok_or_else(|| io::Error::other("child process disappeared"))?;
+    let status = child.wait()?;
+    Ok(Output {
+        status,
+        stdout: stdout?,
+        stderr: stderr?,
+    })
+}
+
+/// Starts `command` without waiting for it. The child is reaped once it exited
+/// and is left running when tailslint quits.
+pub fn spawn_detached(command: &mut Command) -> io::Result<()> {
+    reap();
+    let child = command.spawn()?;
+    register(child, false);
+    Ok(())
+}
+
+/// A command started with `spawn_streaming()`, killed when dropped.
+pub struct Streaming {
+    id: u32,
+}
+
+impl Drop for Streaming {
+    fn drop(&mut self) {
+        if let Some(mut child) = take(self.id) {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+    }
+}
+
+/// Starts `command` with its stdout handed to the caller to read as it comes,
+/// e.g. `journalctl --follow`. It runs until the returned handle is dropped,
+/// and `kill_all()` stops it like any command being waited for.
+pub fn spawn_streaming(command: &mut Command) -> io::Result<(Streaming, ChildStdout)> {
+    reap();
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()?;
+    let stdout = child.stdout.take().expect("stdout is piped");
+    let id = child.id();
+    register(child, true);
+    Ok((Streaming { id }, stdout))
+}
+
+/// Kills every command still being waited for. Detached children are left alone.
+pub fn kill_all() {
+    // also called from the panic hook, where the panicking thread might hold the lock
+    let mut children = match CHILDREN.try_lock() {
+        Ok(children) => children,
+        Err(TryLockError::Poisoned(e)) => e.into_inner(),
+        Err(TryLockError::WouldBlock) => return,
+    };
+
+    for entry in children.iter_mut().filter(|entry| entry.kill_on_exit) {
+        let _ = entry.child.kill();
+    }
+}
+
+/// Makes a panic kill the running commands before the process goes down.
+pub fn kill_all_on_panic() {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        kill_all();
+        default_hook(info);
+    }));
+}
+
+fn register(child: Child, kill_on_exit: bool) {
+    CHILDREN.lock().unwrap().push(Entry {
+        child,
+        kill_on_exit,
+    });
+}
+
+fn take(id: u32) -> Option<Child> {
+    let mut children = CHILDREN.lock().unwrap();
+    let index = children.iter().position(|entry| entry.child.id() == id)?;
+    Some(children.swap_remove(index).child)
+}
+
+/// Forgets the detached children that exited, collecting their exit status.
+fn reap() {
+    CHILDREN
+        .lock()
+        .unwrap()
+        .retain_mut(|entry| entry.kill_on_exit || !matches!(entry.child.try_wait(), Ok(Some(_))));
+}
+
+/// Waits for a reader until `deadline`, `None` if it passed first.
+fn receive(
+    receiver: &mpsc::Receiver<io::Result<Vec<u8>>>,
+    deadline: Option<Instant>,
+) -> Option<io::Result<Vec<u8>>> {
+    let result = match deadline {
+        Some(deadline) => receiver.recv_timeout(deadline.saturating_duration_since(Instant::now())),
+        None => receiver
+            .recv()
+            .map_err(|_| mpsc::RecvTimeoutError::Disconnected),
+    };
+    match result {
+        Ok(output) => Some(output),
+        // the reader panicked
+        Err(mpsc::RecvTimeoutError::Disconnected) => Some(Ok(Vec::new())),
+        Err(mpsc::RecvTimeoutError::Timeout) => None,
+    }
+}
+
+/// Reads a pipe to its end, keeping the first `MAX_OUTPUT` bytes.
+fn read_all(pipe: Option<impl Read>) -> io::Result<Vec<u8>> {
+    let Some(mut pipe) = pipe else {
+        return Ok(Vec::new());
+    };
+
+    let mut buffer = Vec::new();
+    pipe.by_ref().take(MAX_OUTPUT).read_to_end(&mut buffer)?;
+    // the rest is still read so the child doesn't block on a full pipe
+    let discarded = io::copy(&mut pipe, &mut io::sink())?;
+
+    let mut clean = sanitize(&buffer);
+    if discarded > 0 {
+        clean.extend_from_slice(TRUNCATED);
+    }
+    Ok(clean)
+}
+
+/// Drops ANSI escape sequences and every control character but newlines and tabs.
+pub fn sanitize(bytes: &[u8]) -> Vec<u8> {
+    let mut clean = Vec::with_capacity(bytes.len());
+    let mut bytes = bytes.iter().copied();
+
+    while let Some(byte) = bytes.next() {
+        match byte {
+            0x1b => skip_escape(&mut bytes),
+            b'\n' | b'\t' => clean.push(byte),
+            0x00..=0x1f | 0x7f => {}
+            _ => clean.push(byte),
+        }
+    }
+    clean
+}
+
+/// Skips the rest of an escape sequence whose ESC was just read.
+fn skip_escape(bytes: &mut impl Iterator<Item = u8>) {
+    match bytes.next() {
+        // CSI, e.g. colors: parameters up to a final byte in `@`..`~`
+        Some(b'[') => {
+            for byte in bytes.by_ref() {
+                if (0x40..=0x7e).contains(&byte) {
+                    break;
+                }
+            }
+        }
+        // OSC, e.g. window titles and hyperlinks: up to BEL or ESC `\`
+        Some(b']') => {
+            while let Some(byte) = bytes.next() {
+                if byte == 0x07 {
+                    break;
+                }
+                if byte == 0x1b {
+                    bytes.next();
+                    break;
+                }
+            }
+        }
+        // everything else is a two byte sequence
+        _ => {}
+    }
+}
+
+/// A runner answering with canned output, for testing the wrappers without the
+/// programs they run.
+#[cfg(test)]
+pub mod fake {
+    use super::*;
+
+    /// Serializes the tests using the runner, it is shared by the whole process.
+    static LOCK: Mutex<()> = Mutex::new(());
+
+    /// Answers every command starting with one of the prefixes, e.g.
+    /// `"tailscale drive list"`, with its stdout or, if it failed, its stderr.
+    /// Other commands are not found.
+    struct FakeRunner(Vec<(String, bool, String)>);
+
+    impl CommandRunner for FakeRunner {
+        fn run(&self, program: &str, args: &[&str]) -> io::Result<CommandOutput> {
+            let command = [&[program], args].concat().join(" ");
+            let (_, success, output) = self
+                .0
+                .iter()
+                .find(|(prefix, _, _)| command.starts_with(prefix.as_str()))
+                .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, command.clone()))?;
+            Ok(CommandOutput {
+                success: *success,
+                stdout: if *success {
+                    output.clone()
+                } else {
+                    String::new()
+                },
+                stderr: if *success {
+                    String::new()
+                } else {
+                    output.clone()
+                },
+            })
+        }
+    }
+
+    /// Runs `test` with commands answered from `answers`.
+    pub fn with_answers(answers: &[(&str, bool, &str)], test: impl FnOnce()) {
+        let _lock = LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let answers = answers
+            .iter()
+            .map(|(prefix, success, output)| (prefix.to_string(), *success, output.to_string()))
+            .collect();
+        set_runner(FakeRunner(answers));
+
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(test));
+        *RUNNER.write().unwrap() = None;
+        if let Err(panic) = result {
+            std::panic::resume_unwind(panic);
+        }
+    }
+}
+
+/// Arbitrary input for fuzzing the parsers in tests.
+#[cfg(test)]
+pub mod fuzz {
+    /// `count` inputs from a fixed seed, so failures reproduce: random bytes,
+    /// and `samples` with bytes flipped, inserted, removed or truncated, which
+    /// gets further into the parsers than noise does.
+    pub fn inputs(samples: &[&[u8]], count: usize) -> Vec<Vec<u8>> {
+        let mut rng = fastrand::Rng::with_seed(0x7a11_5117);
+        (0..count)
+            .map(|_| match rng.choice(samples) {
+                Some(sample) if rng.bool() => mutate(&mut rng, sample),
+                _ => {
+                    let len = rng.usize(..512);
+                    (0..len).map(|_| rng.u8(..)).collect()
+                }
+            })
+            .collect()
+    }
+
+    fn mutate(rng: &mut fastrand::Rng, sample: &[u8]) -> Vec<u8> {
+        let mut input = sample.to_vec();
+        for _ in 0..rng.usize(1..8) {
+            if input.is_empty() {
+                break;
+            }
+            let at = rng.usize(..input.len());
+            match rng.u8(..4) {
+                0 => input[at] = rng.u8(..),
+                1 => input.insert(at, *rng.choice(b"\x1b[]{}\":,\n\t\0 ").unwrap()),
+                2 => _ = input.remove(at),
+                _ => input.truncate(at),
+            }
+        }
+        input
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sanitize_arbitrary_bytes() {
+        let samples: [&[u8]; 2] = [
+            b"\x1b[1;31merror\x1b[0m: \x1b]8;;https://example.com\x07link\x1b]8;;\x1b\\\n",
+            b"name\tpath\r\nphotos\t/home\x7f\x08\n",
+        ];
+        for input in fuzz::inputs(&samples, 5000) {
+            let clean = sanitize(&input);
+            assert!(clean.len() <= input.len());
+            assert!(
+                clean
+                    .iter()
+                    .all(|&byte| byte == b'\n' || byte == b'\t' || !(byte < 0x20 || byte == 0x7f))
+            );
+            assert_eq!(sanitize(&clean), clean);
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn system_runner_forces_c_locale() {
+        let output = SystemRunner.run("env", &[]).unwrap();
+        let vars: Vec<&str> = output.stdout.lines().collect();
+        assert!(vars.contains(&"LC_ALL=C"));
+        assert!(vars.contains(&"LANG=C"));
+    }
+
+    #[test]
+    fn fake_runner_answers_by_prefix() {
+        fake::with_answers(
+            &[
+                ("tailscale version", true, "1.80.2\n"),
+                ("tailscale up", false, "denied\n"),
+            ],
+            || {
+                let version = run("tailscale", &["version", "--json"]).unwrap();
+                assert!(version.success);
+                assert_eq!(version.stdout, "1.80.2\n");
+
+                let up = run("tailscale", &["up"]).unwrap();
+                assert!(!up.success);
+                assert_eq!(up.stderr, "denied\n");
+
+                let e = run("systemctl", &["status"]).err().unwrap();
+                assert_eq!(e.kind(), io::ErrorKind::NotFound);
+            },
+        );
+    }
+}
@@ -0,0 +1,26 @@
+//! Polling with backoff, for state that trails the command changing it, e.g.
+//! tailscaled accepting connections a moment after `systemctl start` returned.
+
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// The first delay, doubled after every attempt up to `MAX_DELAY`.
+const FIRST_DELAY: Duration = Duration::from_millis(100);
+const MAX_DELAY: Duration = Duration::from_secs(1);
+
+/// Checks `condition` until it holds or `timeout` passed, returning whether it held.
+pub fn until(timeout: Duration, mut condition: impl FnMut() -> bool) -> bool {
+    let deadline = Instant::now() + timeout;
+    let mut delay = FIRST_DELAY;
+    loop {
+        if condition() {
+            return true;
+        }
+        let now = Instant::now();
+        if now >= deadline {
+            return false;
+        }
+        thread::sleep(delay.min(deadline - now));
+        delay = (delay * 2).min(MAX_DELAY);
+    }
+}
@@ -0,0 +1,121 @@
+//! Alerts for peers that are only expected online at certain times of day, e.g.
+//! a backup server that should be up from 01:00 to 03:00. A notification is sent
+//! when a peer is offline inside its window or online outside of it, once each
+//! time it starts deviating. The peers are checked every minute by a
+//! background thread, unless paused from the Settings menu.
+
+use chrono::NaiveTime;
+use serde::{Deserialize, Deserializer};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::Duration;
+
+use crate::MachineData;
+use crate::notification;
+use crate::tailscale::Tailscale;
+
+/// How long to wait between checks.
+const INTERVAL: Duration = Duration::from_secs(60);
+
+/// When one peer is expected to be online, in local time.
+#[derive(Deserialize, Debug, Clone)]
+pub struct PeerSchedule {
+    /// The peer's host name, as shown in the machine list.
+    pub peer: String,
+
+    /// Start of the window, e.g. "01:00".
+    #[serde(deserialize_with = "time_of_day")]
+    pub from: NaiveTime,
+
+    /// End of the window, e.g. "03:00". Windows may wrap around midnight.
+    #[serde(deserialize_with = "time_of_day")]
+    pub until: NaiveTime,
+}
+
+impl PeerSchedule {
+    fn expects_online(&self, now: NaiveTime) -> bool {
+        if self.from <= self.until {
+            self.from <= now && now < self.until
+        } else {
+            now >= self.from || now < self.until
+        }
+    }
+
+    fn window(&self) -> String {
+        format!(
+            "{}–{}",
+            self.from.format("%H:%M"),
+            self.until.format("%H:%M")
+        )
+    }
+}
+
+/// Compares the machine list against the schedules, remembering which peers
+/// were already alerted about.
+pub struct Watcher {
+    schedules: Vec<PeerSchedule>,
+    alerted: Vec<bool>,
+}
+
+impl Watcher {
+    pub fn new(schedules: Vec<PeerSchedule>) -> Self {
+        let alerted = vec![false; schedules.len()];
+        Watcher { schedules, alerted }
+    }
+
+    /// Notifies about every scheduled peer that started deviating from its
+    /// schedule at `now`. Peers missing from `machines` are skipped.
+    pub fn check(&mut self, machines: &[MachineData], now: NaiveTime) {
+        for (schedule, alerted) in self.schedules.iter().zip(&mut self.alerted) {
+            let Some(machine) = machines
+                .iter()
+                .find(|m| m.hostname.as_str() == schedule.peer)
+            else {
+                continue;
+            };
+
+            let expected = schedule.expects_online(now);
+            let deviates = machine.online != expected;
+            if deviates && !*alerted {
+                let (summary, body) = match expected {
+                    true => ("is offline", "It is expected online"),
+                    false => ("is online", "It is only expected online"),
+                };
+                notification::send_state_change(
+                    &format!("{} {summary}", schedule.peer),
+                    &format!("{body} {}.", schedule.window()),
+                );
+            }
+            *alerted = deviates;
+        }
+    }
+}
+
+static PAUSED: AtomicBool = AtomicBool::new(false);
+
+/// Stops or resumes checking the peers, from the next check on.
+pub fn set_paused(paused: bool) {
+    PAUSED.store(paused, Ordering::Relaxed);
+}
+
+/// Starts checking the peers of `schedules` every minute.
+pub fn start(schedules: Vec<PeerSchedule>) {
+    let mut watcher = Watcher::new(schedules);
+    thread::spawn(move || {
+        loop {
+            thread::sleep(INTERVAL);
+            if PAUSED.load(Ordering::Relaxed) {
+                continue;
+            }
+            if let Ok(status) = Tailscale::status() {
+                watcher.check(&status.peers, chrono::Local::now().time());
+            }
+        }
+    });
+}
+
+/// Parses a time of day written as "HH:MM".
+fn time_of_day<'de, D: Deserializer<'de>>(deserializer: D) -> Result<NaiveTime, D::Error> {
+    let time = String::deserialize(deserializer)?;
+    NaiveTime::parse_from_str(&time, "%H:%M").map_err(serde::de::Error::custom)
+}
@@ -0,0 +1,72 @@
+//! The sd_notify protocol, spoken when running as the systemd user service set
+//! up by `install-service`. Readiness, a status line for `systemctl --user
+//! status tailslint` and watchdog pings are sent to `$NOTIFY_SOCKET`; the pings
+//! come from the event loop, so systemd restarts tailslint should it wedge.
+//! Outside of systemd every function does nothing.
+
+use std::os::linux::net::SocketAddrExt;
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::net::{SocketAddr, UnixDatagram};
+use std::time::Duration;
+
+fn socket_address() -> Option<SocketAddr> {
+    let path = std::env::var_os("NOTIFY_SOCKET")?;
+    match path.as_bytes().strip_prefix(b"@") {
+        Some(name) => SocketAddr::from_abstract_name(name).ok(),
+        None => SocketAddr::from_pathname(path).ok(),
+    }
+}
+
+fn send(message: &str) {
+    let Some(address) = socket_address() else {
+        return;
+    };
+    if let Ok(socket) = UnixDatagram::unbound() {
+        let _ = socket.send_to_addr(message.as_bytes(), &address);
+    }
+}
+
+/// Tells systemd that startup finished.
+pub fn ready() {
+    send("READY=1");
+}
+
+/// Sets the status line systemd shows for the service.
+pub fn status(status: &str) {
+    send(&format!("STATUS={}", status.replace('\n', " ")));
+}
+
+/// Tells the watchdog that tailslint is still responsive.
+pub fn watchdog() {
+    send("WATCHDOG=1");
+}
+
+/// The watchdog timeout for commands timing out after `command_timeout`: long
+/// enough for a callback waiting on a few commands that all time out, so a hung
+/// tailscaled doesn't get tailslint restarted over and over. None if commands
+/// may wait forever, then any hung command would trip the watchdog.
+pub fn watchdog_timeout(command_timeout: Duration) -> Option<Duration> {
+    if command_timeout.is_zero() {
+        return None;
+    }
+    Some((command_timeout * 4).max(Duration::from_secs(30)))
+}
+
+/// Changes the watchdog timeout of the running service, e.g. when the command
+/// timeout changed since the unit was written.
+pub fn set_watchdog_timeout(timeout: Duration) {
+    send(&format!("WATCHDOG_USEC={}", timeout.as_micros()));
+}
+
+/// How often the watchdog must be pinged, if systemd set one up for this process.
+pub fn watchdog_interval() -> Option<Duration> {
+    if let Ok(pid) = std::env::var("WATCHDOG_PID")
+        && pid.parse() != Ok(std::process::id())
+    {
+        return None;
+    }
+
+    let usec: u64 = std::env::var("WATCHDOG_USEC").ok()?.parse().ok()?;
+    // pinging at half the timeout leaves room for a slow iteration
+    Some(Duration::from_micros(usec) / 2)
+}
@@ -0,0 +1,1323 @@
+//! Control of systemd units, used to start and stop the tailscaled daemon
+//! itself: `tailscale down` leaves the daemon running, which some users want
+//! fully off, e.g. to save battery. Any other units listed in the config get
+//! the same controls in the Services menu.
+//!
+//! systemd is asked over its D-Bus API, falling back to `systemctl` where the
+//! bus can't be reached, e.g. inside some containers. Units run either by the
+//! system manager or by the user's own (`systemctl --user`). Starting and
+//! stopping a system unit needs privileges, either way they are asked for
+//! through the desktop's polkit agent; user units need none.
+//!
+//! On systems booted with OpenRC, runit or dinit, services are controlled with
+//! `rc-service`, `sv` and `dinitctl` instead, without the systemd extras like
+//! masking, timers or the journal. Units are named as for systemd, e.g.
+//! `tailscaled.service` controls the `tailscaled` service. Without polkit,
+//! changing system services runs those through `doas`, `sudo` or `run0`.
+//! With `escalation` set to `sudo`, `doas` or, on systemd 256 or later,
+//! `run0`, systemd units are changed with e.g. `sudo systemctl …` as well,
+//! instead of over D-Bus. `install-sudoers` sets up sudo or doas for that.
+
+use chrono::{DateTime, Local, NaiveDateTime, TimeZone, Utc};
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use thiserror::Error;
+use zbus::blocking::{Connection, Proxy};
+use zbus::proxy::MethodFlags;
+use zbus::zvariant::OwnedObjectPath;
+
+use crate::process;
+
+/// Defines the possible errors that can occur when controlling a unit.
+#[derive(Error, Debug)]
+pub enum ServiceError {
+    #[error("Failed to execute the service manager: {0}")]
+    CommandError(#[from] std::io::Error),
+
+    #[error("The service manager failed with stderr: {0}")]
+    CommandFailed(String),
+
+    #[error("D-Bus call to systemd failed: {0}")]
+    DBusError(#[from] zbus::Error),
+
+    #[error("Not authorized to control the unit, is a polkit agent running? {0}")]
+    NotAuthorized(String),
+
+    #[error("Not supported by {0}")]
+    Unsupported(&'static str),
+}
+
+/// The init system starting and stopping services.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum InitSystem {
+    Systemd,
+    #[serde(rename = "openrc")]
+    OpenRc,
+    Runit,
+    Dinit,
+}
+
+impl InitSystem {
+    /// Guesses the init system from what PID 1 left behind, systemd if unsure.
+    fn detect() -> Self {
+        // what `sd_booted()` checks
+        if Path::new("/run/systemd/system").is_dir() {
+            return InitSystem::Systemd;
+        }
+        let pid1 = std::fs::read_to_string("/proc/1/comm").unwrap_or_default();
+        match pid1.trim() {
+            "runit" => InitSystem::Runit,
+            "dinit" => InitSystem::Dinit,
+            // OpenRC usually runs under sysvinit or its own openrc-init
+            _ if Path::new("/run/openrc").is_dir() => InitSystem::OpenRc,
+            _ => InitSystem::Systemd,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            InitSystem::Systemd => "systemd",
+            InitSystem::OpenRc => "OpenRC",
+            InitSystem::Runit => "runit",
+            InitSystem::Dinit => "dinit",
+        }
+    }
+}
+
+static INIT_SYSTEM: OnceLock<InitSystem> = OnceLock::new();
+
+/// Uses `init` instead of the detected init system. Only has an effect before
+/// the first unit is controlled.
+pub fn set_init_system(init: InitSystem) {
+    let _ = INIT_SYSTEM.set(init);
+}
+
+/// The configured or detected init system.
+pub fn init_system() -> InitSystem {
+    *INIT_SYSTEM.get_or_init(InitSystem::detect)
+}
+
+/// What the commands changing system services run through on init systems
+/// without polkit, and also on systemd when configured. None of them can ask for
+/// a password on a terminal, so `sudo` uses `$SUDO_ASKPASS` if set and
+/// otherwise needs a rule without one, as `doas` does. `run0` asks through
+/// polkit.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Escalation {
+    Sudo,
+    Doas,
+    Run0,
+    /// Run them directly, e.g. when tailslint itself runs as root.
+    #[serde(rename = "none")]
+    Never,
+}
+
+impl Escalation {
+    /// The first of `doas` (if configured), `sudo` and `run0` installed, none
+    /// when running as root. systemd's own polkit prompts need none of these,
+    /// so there they are only used when configured.
+    fn detect() -> Self {
+        if is_root() {
+            return Escalation::Never;
+        }
+        if in_path("doas") && Path::new("/etc/doas.conf").exists() {
+            Escalation::Doas
+        } else if in_path("sudo") {
+            Escalation::Sudo
+        } else if in_path("run0") {
+            Escalation::Run0
+        } else {
+            Escalation::Never
+        }
+    }
+}
+
+static ESCALATION: OnceLock<Escalation> = OnceLock::new();
+
+/// Uses `escalation` instead of the detected one. Only has an effect before the
+/// first service is changed.
+pub fn set_escalation(escalation: Escalation) {
+    let _ = ESCALATION.set(escalation);
+}
+
+fn escalation() -> Escalation {
+    *ESCALATION.get_or_init(Escalation::detect)
+}
+
+/// Whether tailslint runs with an effective user ID of 0.
+fn is_root() -> bool {
+    let status = std::fs::read_to_string("/proc/self/status").unwrap_or_default();
+    // "Uid:	<real>	<effective>	<saved>	<filesystem>"
+    status
+        .lines()
+        .find_map(|line| line.strip_prefix("Uid:"))
+        .and_then(|ids| ids.split_whitespace().nth(1))
+        == Some("0")
+}
+
+fn in_path(program: &str) -> bool {
+    which(program).is_some()
+}
+
+/// Where `program` is found in `PATH`.
+pub fn which(program: &str) -> Option<PathBuf> {
+    let path = std::env::var_os("PATH")?;
+    std::env::split_paths(&path)
+        .map(|dir| dir.join(program))
+        .find(|path| path.is_file())
+}
+
+/// Which systemd instance manages a unit.
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Scope {
+    /// The system manager, PID 1.
+    #[default]
+    System,
+    /// The user's own manager, as with `systemctl --user`.
+    User,
+}
+
+/// A unit listed under `[[services]]` in the config.
+#[derive(Deserialize, Debug, Clone)]
+pub struct WatchedUnit {
+    /// The unit's name, e.g. "postgresql.service".
+    pub unit: String,
+
+    /// "system" or "user", system by default.
+    #[serde(default)]
+    pub scope: Scope,
+}
+
+/// What systemd reports about a unit.
+#[derive(Debug, Default)]
+pub struct UnitStatus {
+    /// "loaded", or e.g. "not-found" for units systemd doesn't know.
+    pub load_state: String,
+    /// e.g. "active", "inactive" or "failed".
+    pub active_state: String,
+    /// e.g. "running" or "dead", depends on the unit type.
+    pub sub_state: String,
+    /// Whether the unit starts at boot, e.g. "enabled", "disabled" or "static".
+    pub unit_file_state: String,
+    /// The main process, 0 while there is none.
+    pub main_pid: u32,
+    /// The memory used by the unit's processes, if accounted.
+    pub memory_current: Option<u64>,
+    /// The most memory used since the unit started, if accounted.
+    pub memory_peak: Option<u64>,
+    /// The CPU time used since the unit started, if accounted.
+    pub cpu_usage: Option<Duration>,
+    /// When the unit last became active, none if it never was.
+    pub active_since: Option<DateTime<Utc>>,
+    /// How often systemd restarted the service automatically, e.g. after a
+    /// crash with `Restart=on-failure`.
+    pub restarts: u32,
+    /// When a timer unit next triggers, none if it isn't scheduled on the
+    /// calendar, e.g. while stopped.
+    pub next_elapse: Option<DateTime<Utc>>,
+}
+
+impl UnitStatus {
+    /// Whether the unit is running, like `systemctl is-active`.
+    pub fn is_active(&self) -> bool {
+        matches!(self.active_state.as_str(), "active" | "reloading")
+    }
+
+    /// Whether the unit starts at boot, like `systemctl is-enabled`.
+    pub fn is_enabled(&self) -> bool {
+        self.unit_file_state == "enabled"
+    }
+
+    /// Whether the unit stopped because it failed, e.g. its main process crashed.
+    pub fn is_failed(&self) -> bool {
+        self.active_state == "failed"
+    }
+
+    /// Whether the unit is masked, so nothing can start it.
+    pub fn is_masked(&self) -> bool {
+        self.unit_file_state.starts_with("masked")
+    }
+
+    /// Whether `set_enabled()` can change that, false e.g. for static units
+    /// without an `[Install]` section.
+    pub fn can_enable(&self) -> bool {
+        matches!(self.unit_file_state.as_str(), "enabled" | "disabled")
+    }
+}
+
+/// A systemd unit, e.g. `tailscaled.service`.
+pub struct Service {
+    unit: String,
+    scope: Scope,
+}
+
+impl Service {
+    /// A unit of the system manager.
+    pub fn new(unit: &str) -> Self {
+        Self::with_scope(unit, Scope::System)
+    }
+
+    pub fn with_scope(unit: &str, scope: Scope) -> Self {
+        Service {
+            unit: unit.to_string(),
+            scope,
+        }
+    }
+
+    pub fn unit(&self) -> &str {
+        &self.unit
+    }
+
+    /// The unit the Tailscale daemon runs as.
+    pub fn tailscaled() -> Self {
+        Self::new("tailscaled.service")
+    }
+
+    /// Whether the init system knows the unit.
+    pub fn is_installed(&self) -> bool {
+        self.status()
+            .is_ok_and(|status| status.load_state == "loaded")
+    }
+
+    /// Whether the unit is running.
+    pub fn is_active(&self) -> bool {
+        self.status().is_ok_and(|status| status.is_active())
+    }
+
+    /// Reads the unit's state, main process and resource use.
+    pub fn status(&self) -> Result<UnitStatus, ServiceError> {
+        backend(self.scope).status(&self.unit)
+    }
+
+    /// Starts the unit, returning once systemd queued the job.
+    pub fn start(&self) -> Result<(), ServiceError> {
+        backend(self.scope).start(&self.unit)
+    }
+
+    /// Stops the unit, returning once systemd queued the job.
+    pub fn stop(&self) -> Result<(), ServiceError> {
+        backend(self.scope).stop(&self.unit)
+    }
+
+    /// The timer unit of the same name activating this service, e.g.
+    /// `backup.timer` for `backup.service`, if one is installed.
+    pub fn timer(&self) -> Option<Service> {
+        if init_system() != InitSystem::Systemd {
+            return None;
+        }
+        let name = self.unit.strip_suffix(".service")?;
+        let timer = Service::with_scope(&format!("{name}.timer"), self.scope);
+        timer.is_installed().then_some(timer)
+    }
+
+    /// Restarts the unit, or starts it if it isn't running.
+    pub fn restart(&self) -> Result<(), ServiceError> {
+        backend(self.scope).restart(&self.unit)
+    }
+
+    /// The unit's last `lines` journal messages, oldest first, from `journalctl`.
+    pub fn recent_logs(&self, lines: usize) -> Result<String, ServiceError> {
+        systemd_only()?;
+        let lines = lines.to_string();
+        let unit_flag = match self.scope {
+            Scope::System => "--unit",
+            Scope::User => "--user-unit",
+        };
+        run(
+            "journalctl",
+            &[
+                unit_flag,
+                &self.unit,
+                "--lines",
+                &lines,
+                "--no-pager",
+                "--output=cat",
+            ],
+        )
+    }
+
+    /// The units this one pulls in, or with `reverse` the ones pulling it in,
+    /// as a depth-first tree of `(depth, unit)` from `systemctl list-dependencies`.
+    /// Stopping the unit also stops the reverse dependencies bound to it.
+    pub fn dependencies(&self, reverse: bool) -> Result<Vec<(usize, String)>, ServiceError> {
+        systemd_only()?;
+        let mut args = vec!["list-dependencies", "--plain", "--no-pager"];
+        if reverse {
+            args.push("--reverse");
+        }
+        let systemctl = Systemctl {
+            scope: self.scope,
+            escalate: false,
+        };
+        let stdout = systemctl.run(&args, &self.unit)?;
+        Ok(parse_dependencies(&stdout))
+    }
+
+    /// Enables or disables starting the unit at boot, like `systemctl enable`
+    /// and `systemctl disable`.
+    pub fn set_enabled(&self, enabled: bool) -> Result<(), ServiceError> {
+        backend(self.scope).set_enabled(&self.unit, enabled)
+    }
+
+    /// Masks the unit so neither dependencies, sockets nor D-Bus activation can
+    /// start it, or unmasks it again, like `systemctl mask` and `systemctl unmask`.
+    pub fn set_masked(&self, masked: bool) -> Result<(), ServiceError> {
+        backend(self.scope).set_masked(&self.unit, masked)
+    }
+}
+
+/// Fails for the systemd extras on other init systems.
+fn systemd_only() -> Result<(), ServiceError> {
+    match init_system() {
+        InitSystem::Systemd => Ok(()),
+        init => Err(ServiceError::Unsupported(init.name())),
+    }
+}
+
+/// Runs `program` and returns its stdout, failing with its stderr.
+fn run(program: &str, args: &[&str]) -> Result<String, ServiceError> {
+    let output = process::run(program, args)?;
+
+    if !output.success {
+        return Err(ServiceError::CommandFailed(output.stderr));
+    }
+    Ok(output.stdout)
+}
+
+/// Runs `program` like `run()`, through the configured escalation for system
+/// services. User services are the user's own and need none.
+fn run_privileged(scope: Scope, program: &str, args: &[&str]) -> Result<String, ServiceError> {
+    if scope == Scope::User {
+        return run(program, args);
+    }
+
+    match escalated_command(escalation(), program, args) {
+        Some((escalation, args)) => {
+            let args: Vec<&str> = args.iter().map(String::as_str).collect();
+            run(escalation, &args)
+        }
+        None => run(program, args),
+    }
+}
+
+/// The command `run_privileged()` runs `program` with through `escalation`, as
+/// (program, arguments), none if `program` runs directly. `program` is passed
+/// by its absolute path: doas only allows a command spelled exactly like its
+/// rule, and the rules `install-doas-rule` writes name programs by path.
+pub fn escalated_command(
+    escalation: Escalation,
+    program: &str,
+    args: &[&str],
+) -> Option<(&'static str, Vec<String>)> {
+    let (escalation, flags): (_, &[&str]) = match escalation {
+        Escalation::Never => return None,
+        Escalation::Sudo if std::env::var_os("SUDO_ASKPASS").is_some() => ("sudo", &["-A"]),
+        Escalation::Sudo => ("sudo", &["-n"]),
+        Escalation::Doas => ("doas", &["-n"]),
+        Escalation::Run0 => ("run0", &[]),
+    };
+    let program = which(program).map_or(program.to_string(), |path| {
+        path.to_string_lossy().to_string()
+    });
+
+    let mut command: Vec<String> = flags.iter().map(|flag| flag.to_string()).collect();
+    command.extend(["--".to_string(), program]);
+    command.extend(args.iter().map(|arg| arg.to_string()));
+    Some((escalation, command))
+}
+
+/// A way of controlling services, for systemd or another init system.
+trait InitBackend: Send + Sync {
+    fn status(&self, unit: &str) -> Result<UnitStatus, ServiceError>;
+    fn start(&self, unit: &str) -> Result<(), ServiceError>;
+    fn stop(&self, unit: &str) -> Result<(), ServiceError>;
+    fn restart(&self, unit: &str) -> Result<(), ServiceError>;
+    fn set_enabled(&self, unit: &str, enabled: bool) -> Result<(), ServiceError>;
+    fn set_masked(&self, unit: &str, masked: bool) -> Result<(), ServiceError>;
+}
+
+/// For systemd e.g. `sudo systemctl` if configured, otherwise the D-Bus API if the
+/// scope's bus can be reached and `systemctl` if not, with user units managed
+/// over the session bus.
+fn backend(scope: Scope) -> &'static dyn InitBackend {
+    static SYSTEM: OnceLock<Box<dyn InitBackend>> = OnceLock::new();
+    static USER: OnceLock<Box<dyn InitBackend>> = OnceLock::new();
+
+    let (backend, connect): (_, fn() -> zbus::Result<Connection>) = match scope {
+        Scope::System => (&SYSTEM, Connection::system),
+        Scope::User => (&USER, Connection::session),
+    };
+    backend
+        .get_or_init(|| match init_system() {
+            // decided before connecting, so escalating works without the bus
+            InitSystem::Systemd if escalates_systemctl() => Box::new(Systemctl {
+                scope,
+                escalate: true,
+            }),
+            InitSystem::Systemd => match connect() {
+                Ok(connection) => Box::new(DBus { connection }),
+                Err(_) => Box::new(Systemctl {
+                    scope,
+                    escalate: false,
+                }),
+            },
+            InitSystem::OpenRc => Box::new(OpenRc { scope }),
+            InitSystem::Runit => Box::new(Runit { scope }),
+            InitSystem::Dinit => Box::new(Dinit { scope }),
+        })
+        .as_ref()
+}
+
+/// Whether systemd units are changed through the escalation, e.g. with `sudo
+/// systemctl`, only when configured and for run0 only where it exists, i.e.
+/// on systemd 256 or later.
+fn escalates_systemctl() -> bool {
+    match ESCALATION.get() {
+        Some(Escalation::Sudo | Escalation::Doas) => true,
+        Some(Escalation::Run0) => systemd_version() >= 256,
+        _ => false,
+    }
+}
+
+/// The commands `run_privileged()` runs to change the system unit `unit` with
+/// the current init system, as (program, arguments): starting, stopping and
+/// restarting it, starting it at boot or not and, with systemd, masking it.
+/// These are what `install-sudoers` allows. On runit that means linking the
+/// definition into the service directory and removing the link, the `ln` and
+/// `rm` commands allowed are only those for the unit's own link.
+pub fn privileged_commands(unit: &str) -> Vec<(&'static str, Vec<String>)> {
+    let name = service_name(unit);
+    let command =
+        |program, args: &[&str]| (program, args.iter().map(|arg| arg.to_string()).collect());
+
+    match init_system() {
+        InitSystem::Systemd => [
+            "start", "stop", "restart", "enable", "disable", "mask", "unmask",
+        ]
+        .map(|verb| command("systemctl", &[verb, unit]))
+        .into(),
+        InitSystem::OpenRc => {
+            let mut commands: Vec<_> = ["start", "stop", "restart"]
+                .map(|verb| command("rc-service", &[name, verb]))
+                .into();
+            commands
+                .extend(["add", "del"].map(|verb| command("rc-update", &[verb, name, "default"])));
+            commands
+        }
+        InitSystem::Runit => {
+            let runit = Runit {
+                scope: Scope::System,
+            };
+            let link = runit.service_dir().join(name);
+            let link = link.to_string_lossy();
+            let mut commands: Vec<_> = ["up", "down", "restart"]
+                .map(|verb| command("sv", &[verb, &link]))
+                .into();
+            commands.push(command("rm", &["--", &link]));
+            // a service that isn't installed can't be linked
+            if let Some(definition) = runit.available(name) {
+                commands.push(command(
+                    "ln",
+                    &["-s", "--", &definition.to_string_lossy(), &link],
+                ));
+            }
+            commands
+        }
+        InitSystem::Dinit => ["start", "stop", "restart", "enable", "disable"]
+            .map(|verb| command("dinitctl", &[verb, name]))
+            .into(),
+    }
+}
+
+/// Reads the tree of `systemctl list-dependencies` as `(depth, unit)`. The first
+/// line is the unit itself, every level below it is indented by a two
+/// character cell, spaces with `--plain` or e.g. "├─" and "│ " without, and
+/// units keep their names as printed, e.g. `-.slice`.
+fn parse_dependencies(stdout: &str) -> Vec<(usize, String)> {
+    const CELLS: [&str; 7] = ["  ", "│ ", "├─", "└─", "| ", "|-", "`-"];
+
+    stdout
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            // the active state bullet, only printed without --plain
+            let mut rest = line
+                .strip_prefix(['●', '○', '×', '*'])
+                .and_then(|rest| rest.strip_prefix(' '))
+                .unwrap_or(line);
+            let mut cells: usize = 0;
+            while let Some(next) = CELLS.iter().find_map(|cell| rest.strip_prefix(cell)) {
+                rest = next;
+                cells += 1;
+            }
+            let unit = rest.trim();
+            (!unit.is_empty()).then(|| (cells.saturating_sub(1), unit.to_string()))
+        })
+        .collect()
+}
+
+/// The version of the running systemd, from `systemctl --version`, 0 if unknown.
+fn systemd_version() -> u32 {
+    // "systemd 256 (256.4-1-arch)"
+    run("systemctl", &["--version"])
+        .ok()
+        .and_then(|stdout| stdout.split_whitespace().nth(1)?.parse().ok())
+        .unwrap_or_default()
+}
+
+/// The name other init systems know a unit by, `tailscaled` for `tailscaled.service`.
+fn service_name(unit: &str) -> &str {
+    unit.strip_suffix(".service").unwrap_or(unit)
+}
+
+const DESTINATION: &str = "org.freedesktop.systemd1";
+
+/// The symlinks the unit file methods, e.g. `EnableUnitFiles`, changed, as
+/// (type, file name, destination).
+type UnitFileChanges = Vec<(String, String, String)>;
+
+/// Calls `org.freedesktop.systemd1` on the system or session bus, so
+/// properties arrive typed instead of as text, without spawning a process.
+struct DBus {
+    connection: Connection,
+}
+
+impl DBus {
+    fn proxy(&self, path: OwnedObjectPath, interface: &'static str) -> zbus::Result<Proxy<'_>> {
+        Proxy::new(&self.connection, DESTINATION, path, interface)
+    }
+
+    fn manager(&self) -> zbus::Result<Proxy<'_>> {
+        Proxy::new(
+            &self.connection,
+            DESTINATION,
+            "/org/freedesktop/systemd1",
+            "org.freedesktop.systemd1.Manager",
+        )
+    }
+
+    /// Calls a manager method needing privileges, e.g. `StartUnit`. polkit may
+    /// ask to authenticate first, through the agent of the desktop session.
+    fn privileged<B, R>(&self, method: &str, body: &B) -> Result<Option<R>, ServiceError>
+    where
+        B: serde::Serialize + zbus::zvariant::DynamicType,
+        R: for<'d> zbus::zvariant::DynamicDeserialize<'d>,
+    {
+        let result =
+            self.manager()?
+                .call_with_flags(method, MethodFlags::AllowInteractiveAuth.into(), body);
+
+        match result {
+            Ok(reply) => Ok(reply),
+            // denied, or no agent was there to ask
+            Err(zbus::Error::MethodError(name, message, _))
+                if matches!(
+                    name.as_str(),
+                    "org.freedesktop.DBus.Error.AccessDenied"
+                        | "org.freedesktop.DBus.Error.InteractiveAuthorizationRequired"
+                ) =>
+            {
+                Err(ServiceError::NotAuthorized(message.unwrap_or_default()))
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+impl InitBackend for DBus {
+    fn status(&self, unit: &str) -> Result<UnitStatus, ServiceError> {
+        // loads the unit if needed, unknown units get a path with LoadState "not-found"
+        let path: OwnedObjectPath = self.manager()?.call("LoadUnit", &(unit,))?;
+        let properties = self.proxy(path.clone(), "org.freedesktop.systemd1.Unit")?;
+        // microseconds since the epoch, 0 if the unit never was active
+        let active_since = properties
+            .get_property::<u64>("ActiveEnterTimestamp")
+            .ok()
+            .filter(|micros| *micros != 0)
+            .and_then(|micros| DateTime::from_timestamp_micros(micros as i64));
+        let mut status = UnitStatus {
+            active_since,
+            load_state: properties.get_property("LoadState")?,
+            active_state: properties.get_property("ActiveState")?,
+            sub_state: properties.get_property("SubState")?,
+            unit_file_state: properties.get_property("UnitFileState")?,
+            ..Default::default()
+        };
+
+        // only service units have these
+        if unit.ends_with(".service") {
+            let service = self.proxy(path, "org.freedesktop.systemd1.Service")?;
+            status.main_pid = service.get_property("MainPID").unwrap_or_default();
+            status.restarts = service.get_property("NRestarts").unwrap_or_default();
+            // u64::MAX stands for "not set", e.g. without accounting
+            let counter = |name: &str| {
+                service
+                    .get_property::<u64>(name)
+                    .ok()
+                    .filter(|value| *value != u64::MAX)
+            };
+            status.memory_current = counter("MemoryCurrent");
+            status.memory_peak = counter("MemoryPeak");
+            status.cpu_usage = counter("CPUUsageNSec").map(Duration::from_nanos);
+        } else if unit.ends_with(".timer") {
+            let timer = self.proxy(path, "org.freedesktop.systemd1.Timer")?;
+            status.next_elapse = timer
+                .get_property::<u64>("NextElapseUSecRealtime")
+                .ok()
+                .filter(|micros| *micros != 0)
+                .and_then(|micros| DateTime::from_timestamp_micros(micros as i64));
+        }
+        Ok(status)
+    }
+
+    fn start(&self, unit: &str) -> Result<(), ServiceError> {
+        let _job: Option<OwnedObjectPath> = self.privileged("StartUnit", &(unit, "replace"))?;
+        Ok(())
+    }
+
+    fn stop(&self, unit: &str) -> Result<(), ServiceError> {
+        let _job: Option<OwnedObjectPath> = self.privileged("StopUnit", &(unit, "replace"))?;
+        Ok(())
+    }
+
+    fn restart(&self, unit: &str) -> Result<(), ServiceError> {
+        let _job: Option<OwnedObjectPath> = self.privileged("RestartUnit", &(unit, "replace"))?;
+        Ok(())
+    }
+
+    fn set_enabled(&self, unit: &str, enabled: bool) -> Result<(), ServiceError> {
+        // the symlinks changed, reloading lets systemd see it like `systemctl enable` does
+        let files = [unit];
+        match enabled {
+            true => {
+                let _: Option<(bool, UnitFileChanges)> =
+                    self.privileged("EnableUnitFiles", &(&files[..], false, false))?;
+            }
+            false => {
+                let _: Option<UnitFileChanges> =
+                    self.privileged("DisableUnitFiles", &(&files[..], false))?;
+            }
+        }
+        let _: Option<()> = self.privileged("Reload", &())?;
+        Ok(())
+    }
+
+    fn set_masked(&self, unit: &str, masked: bool) -> Result<(), ServiceError> {
+        let files = [unit];
+        let _: Option<UnitFileChanges> = match masked {
+            true => self.privileged("MaskUnitFiles", &(&files[..], false, false))?,
+            false => self.privileged("UnmaskUnitFiles", &(&files[..], false))?,
+        };
+        let _: Option<()> = self.privileged("Reload", &())?;
+        Ok(())
+    }
+}
+
+/// Runs `systemctl`, reading properties from the `key=value` output of
+/// `systemctl show`.
+struct Systemctl {
+    scope: Scope,
+    /// Whether changes run through the configured escalation, i.e. `run0`,
+    /// instead of `systemctl` asking polkit itself.
+    escalate: bool,
+}
+
+impl Systemctl {
+    /// `[--user] <args> <unit>`.
+    fn args<'a>(&self, args: &[&'a str], unit: &'a str) -> Vec<&'a str> {
+        let mut args = match self.scope {
+            Scope::System => args.to_vec(),
+            Scope::User => [&["--user"], args].concat(),
+        };
+        args.push(unit);
+        args
+    }
+
+    /// Runs `systemctl [--user] <args> <unit>` and returns its stdout.
+    fn run(&self, args: &[&str], unit: &str) -> Result<String, ServiceError> {
+        run("systemctl", &self.args(args, unit))
+    }
+
+    /// Runs `systemctl [--user] <args> <unit>` to change the unit.
+    fn control(&self, args: &[&str], unit: &str) -> Result<String, ServiceError> {
+        match self.escalate {
+            true => run_privileged(self.scope, "systemctl", &self.args(args, unit)),
+            false => self.run(args, unit),
+        }
+    }
+}
+
+impl InitBackend for Systemctl {
+    fn status(&self, unit: &str) -> Result<UnitStatus, ServiceError> {
+        // properties older systemd doesn't know, e.g. MemoryPeak before 256, are left out
+        const PROPERTIES: &str = "--property=LoadState,ActiveState,SubState,UnitFileState,MainPID,MemoryCurrent,MemoryPeak,CPUUsageNSec,ActiveEnterTimestamp,NRestarts,NextElapseUSecRealtime";
+        // `--timestamp=unix` is systemd 248 or later, older ones print local times
+        static NO_UNIX_TIMESTAMPS: AtomicBool = AtomicBool::new(false);
+
+        let stdout = match NO_UNIX_TIMESTAMPS.load(Ordering::Relaxed) {
+            true => self.run(&["show", PROPERTIES], unit)?,
+            false => match self.run(&["show", "--timestamp=unix", PROPERTIES], unit) {
+                Err(ServiceError::CommandFailed(stderr)) if stderr.contains("--timestamp") => {
+                    NO_UNIX_TIMESTAMPS.store(true, Ordering::Relaxed);
+                    self.run(&["show", PROPERTIES], unit)?
+                }
+                result => result?,
+            },
+        };
+
+        let property = |name: &str| {
+            stdout
+                .lines()
+                .find_map(|line| line.strip_prefix(name)?.strip_prefix('='))
+                .unwrap_or_default()
+                .trim()
+                .to_string()
+        };
+        // "@<seconds>", or before 248 "Tue 2025-10-14 08:00:00 CEST", empty if not set
+        let timestamp = |name: &str| match property(name).strip_prefix('@') {
+            Some(seconds) => seconds
+                .parse()
+                .ok()
+                .and_then(|seconds| DateTime::from_timestamp(seconds, 0)),
+            None => local_timestamp(&property(name)),
+        };
+        // "[not set]" without accounting, before 244 u64::MAX
+        let counter = |name: &str| {
+            property(name)
+                .parse::<u64>()
+                .ok()
+                .filter(|value| *value != u64::MAX)
+        };
+        Ok(UnitStatus {
+            load_state: property("LoadState"),
+            active_state: property("ActiveState"),
+            sub_state: property("SubState"),
+            unit_file_state: property("UnitFileState"),
+            main_pid: property("MainPID").parse().unwrap_or_default(),
+            memory_current: counter("MemoryCurrent"),
+            memory_peak: counter("MemoryPeak"),
+            cpu_usage: counter("CPUUsageNSec").map(Duration::from_nanos),
+            active_since: timestamp("ActiveEnterTimestamp"),
+            restarts: property("NRestarts").parse().unwrap_or_default(),
+            next_elapse: timestamp("NextElapseUSecRealtime"),
+        })
+    }
+
+    fn start(&self, unit: &str) -> Result<(), ServiceError> {
+        self.control(&["start"], unit).map(|_| ())
+    }
+
+    fn stop(&self, unit: &str) -> Result<(), ServiceError> {
+        self.control(&["stop"], unit).map(|_| ())
+    }
+
+    fn restart(&self, unit: &str) -> Result<(), ServiceError> {
+        self.control(&["restart"], unit).map(|_| ())
+    }
+
+    fn set_enabled(&self, unit: &str, enabled: bool) -> Result<(), ServiceError> {
+        let command = if enabled { "enable" } else { "disable" };
+        self.control(&[command], unit).map(|_| ())
+    }
+
+    fn set_masked(&self, unit: &str, masked: bool) -> Result<(), ServiceError> {
+        let command = if masked { "mask" } else { "unmask" };
+        self.control(&[command], unit).map(|_| ())
+    }
+}
+
+/// Reads a timestamp systemctl printed in the local time zone, e.g. "Tue
+/// 2025-10-14 08:00:00 CEST", the zone's abbreviation is taken to be the local
+/// zone unless it is UTC.
+fn local_timestamp(timestamp: &str) -> Option<DateTime<Utc>> {
+    let mut parts = timestamp.split_whitespace().skip(1);
+    let time = format!("{} {}", parts.next()?, parts.next()?);
+    let time = NaiveDateTime::parse_from_str(&time, "%Y-%m-%d %H:%M:%S").ok()?;
+    match parts.next() {
+        Some("UTC") => Some(time.and_utc()),
+        _ => Local
+            .from_local_datetime(&time)
+            .earliest()
+            .map(|time| time.with_timezone(&Utc)),
+    }
+}
+
+/// Runs OpenRC's `rc-service` and `rc-update`, services start at boot by being
+/// in the default runlevel.
+struct OpenRc {
+    scope: Scope,
+}
+
+impl OpenRc {
+    /// `[--user] <args>`.
+    fn args<'a>(&self, args: &[&'a str]) -> Vec<&'a str> {
+        match self.scope {
+            Scope::System => args.to_vec(),
+            Scope::User => [&["--user"], args].concat(),
+        }
+    }
+
+    /// Runs `program [--user] <args>`.
+    fn run(&self, program: &str, args: &[&str]) -> Result<String, ServiceError> {
+        run(program, &self.args(args))
+    }
+
+    /// Runs `program [--user] <args>` to change a service.
+    fn control(&self, program: &str, args: &[&str]) -> Result<String, ServiceError> {
+        run_privileged(self.scope, program, &self.args(args))
+    }
+
+    fn is_enabled(&self, name: &str) -> bool {
+        // " tailscaled | default" for every service in the runlevel
+        self.run("rc-update", &["show", "default"])
+            .is_ok_and(|stdout| {
+                stdout
+                    .lines()
+                    .any(|line| line.split('|').next().map(str::trim) == Some(name))
+            })
+    }
+}
+
+impl InitBackend for OpenRc {
+    fn status(&self, unit: &str) -> Result<UnitStatus, ServiceError> {
+        let name = service_name(unit);
+        // exits non-zero for anything but started, so stdout is read either way
+        let output = process::run("rc-service", &self.args(&[name, "status"]))?;
+
+        // " * status: started"
+        let Some(state) = output
+            .stdout
+            .lines()
+            .find_map(|line| line.split_once("status:"))
+            .map(|(_, state)| state.trim().to_string())
+        else {
+            return Ok(UnitStatus {
+                load_state: "not-found".into(),
+                active_state: "inactive".into(),
+                sub_state: "dead".into(),
+                ..Default::default()
+            });
+        };
+
+        let active_state = match state.as_str() {
+            "started" => "active",
+            "starting" => "activating",
+            "stopping" => "deactivating",
+            "crashed" => "failed",
+            _ => "inactive",
+        };
+        let unit_file_state = match self.is_enabled(name) {
+            true => "enabled",
+            false => "disabled",
+        };
+        Ok(UnitStatus {
+            load_state: "loaded".into(),
+            active_state: active_state.into(),
+            sub_state: state,
+            unit_file_state: unit_file_state.into(),
+            ..Default::default()
+        })
+    }
+
+    fn start(&self, unit: &str) -> Result<(), ServiceError> {
+        self.control("rc-service", &[service_name(unit), "start"])
+            .map(|_| ())
+    }
+
+    fn stop(&self, unit: &str) -> Result<(), ServiceError> {
+        self.control("rc-service", &[service_name(unit), "stop"])
+            .map(|_| ())
+    }
+
+    fn restart(&self, unit: &str) -> Result<(), ServiceError> {
+        self.control("rc-service", &[service_name(unit), "restart"])
+            .map(|_| ())
+    }
+
+    fn set_enabled(&self, unit: &str, enabled: bool) -> Result<(), ServiceError> {
+        let command = if enabled { "add" } else { "del" };
+        self.control("rc-update", &[command, service_name(unit), "default"])
+            .map(|_| ())
+    }
+
+    fn set_masked(&self, _unit: &str, _masked: bool) -> Result<(), ServiceError> {
+        Err(ServiceError::Unsupported(InitSystem::OpenRc.name()))
+    }
+}
+
+/// Runs runit's `sv`. Services are defined in a directory like `/etc/sv` and
+/// run, also at boot, once linked into the supervised one like `/var/service`.
+/// Per-user services are supervised in `~/service`.
+struct Runit {
+    scope: Scope,
+}
+
+impl Runit {
+    /// The supervised directory, `$SVDIR` if set like `sv` itself does.
+    fn service_dir(&self) -> PathBuf {
+        if self.scope == Scope::User {
+            let home = std::env::var_os("HOME").map(PathBuf::from);
+            return home.unwrap_or_default().join("service");
+        }
+        if let Some(dir) = std::env::var_os("SVDIR") {
+            return dir.into();
+        }
+        // Artix keeps it in /run, Void in /var
+        ["/run/runit/service", "/var/service"]
+            .into_iter()
+            .map(PathBuf::from)
+            .find(|dir| dir.is_dir())
+            .unwrap_or_else(|| "/var/service".into())
+    }
+
+    /// Where the service's definition is, linked or not.
+    fn definition(&self, name: &str) -> Option<PathBuf> {
+        let linked = self.service_dir().join(name);
+        match linked.is_dir() {
+            true => Some(linked),
+            false => self.available(name),
+        }
+    }
+
+    /// Where the service is defined before being linked, e.g. `/etc/sv/sshd`.
+    /// User services have no such place.
+    fn available(&self, name: &str) -> Option<PathBuf> {
+        let available = match self.scope {
+            Scope::System => vec!["/etc/sv", "/etc/runit/sv"],
+            Scope::User => Vec::new(),
+        };
+        available
+            .into_iter()
+            .map(|dir| Path::new(dir).join(name))
+            .find(|dir| dir.is_dir())
+    }
+
+    /// Runs `sv <command> <service directory>`, by path so `$SVDIR` doesn't matter.
+    fn sv(&self, command: &str, unit: &str) -> Result<String, ServiceError> {
+        let path = self.service_dir().join(service_name(unit));
+        let args = [command, &path.to_string_lossy()];
+        match command {
+            "status" => run("sv", &args),
+            _ => run_privileged(self.scope, "sv", &args),
+        }
+    }
+}
+
+impl InitBackend for Runit {
+    fn status(&self, unit: &str) -> Result<UnitStatus, ServiceError> {
+        let name = service_name(unit);
+        let Some(definition) = self.definition(name) else {
+            return Ok(UnitStatus {
+                load_state: "not-found".into(),
+                active_state: "inactive".into(),
+                sub_state: "dead".into(),
+                ..Default::default()
+            });
+        };
+        let linked = definition.parent() == Some(self.service_dir().as_path());
+        let mut status = UnitStatus {
+            load_state: "loaded".into(),
+            active_state: "inactive".into(),
+            sub_state: "dead".into(),
+            unit_file_state: if linked { "enabled" } else { "disabled" }.into(),
+            ..Default::default()
+        };
+        if !linked {
+            return Ok(status);
+        }
+
+        // "run: /var/service/sshd: (pid 812) 7980s; run: log: (pid 811) 7980s"
+        let stdout = self.sv("status", unit)?;
+        let first = stdout.split(';').next().unwrap_or_default();
+        let (state, rest) = first.split_once(':').unwrap_or_default();
+        (status.active_state, status.sub_state) = match state {
+            "run" => ("active".into(), "running".into()),
+            "finish" => ("deactivating".into(), "finishing".into()),
+            _ => ("inactive".into(), "dead".into()),
+        };
+        status.main_pid = rest
+            .split_once("(pid ")
+            .and_then(|(_, pid)| pid.split(')').next()?.parse().ok())
+            .unwrap_or_default();
+        let seconds = rest
+            .split_whitespace()
+            .find_map(|word| word.strip_suffix('s')?.parse::<i64>().ok());
+        if let Some(seconds) = seconds
+            && status.is_active()
+        {
+            status.active_since = Some(Utc::now() - chrono::TimeDelta::seconds(seconds));
+        }
+        Ok(status)
+    }
+
+    fn start(&self, unit: &str) -> Result<(), ServiceError> {
+        self.sv("up", unit).map(|_| ())
+    }
+
+    fn stop(&self, unit: &str) -> Result<(), ServiceError> {
+        self.sv("down", unit).map(|_| ())
+    }
+
+    fn restart(&self, unit: &str) -> Result<(), ServiceError> {
+        self.sv("restart", unit).map(|_| ())
+    }
+
+    fn set_enabled(&self, unit: &str, enabled: bool) -> Result<(), ServiceError> {
+        let name = service_name(unit);
+        let link = self.service_dir().join(name);
+        let link = link.to_string_lossy();
+        if !enabled {
+            return run_privileged(self.scope, "rm", &["--", &link]).map(|_| ());
+        }
+        let definition = self
+            .definition(name)
+            .ok_or_else(|| ServiceError::CommandFailed(format!("no service named {name}")))?;
+        let definition = definition.to_string_lossy();
+        run_privileged(self.scope, "ln", &["-s", "--", &definition, &link]).map(|_| ())
+    }
+
+    fn set_masked(&self, _unit: &str, _masked: bool) -> Result<(), ServiceError> {
+        Err(ServiceError::Unsupported(InitSystem::Runit.name()))
+    }
+}
+
+/// Runs `dinitctl`, services start at boot by being enabled for the boot service.
+struct Dinit {
+    scope: Scope,
+}
+
+impl Dinit {
+    /// Runs `dinitctl [--user] <command> <service>`.
+    fn run(&self, command: &str, unit: &str) -> Result<String, ServiceError> {
+        let mut args = match self.scope {
+            Scope::System => vec![],
+            Scope::User => vec!["--user"],
+        };
+        args.extend([command, service_name(unit)]);
+        match command {
+            "status" => run("dinitctl", &args),
+            _ => run_privileged(self.scope, "dinitctl", &args),
+        }
+    }
+
+    /// Whether the boot service waits for `name`, as `dinitctl enable` sets up.
+    fn is_enabled(&self, name: &str) -> bool {
+        let boot_dir = match self.scope {
+            Scope::System => PathBuf::from("/etc/dinit.d/boot.d"),
+            Scope::User => crate::config::xdg_config_home()
+                .unwrap_or_default()
+                .join("dinit.d/boot.d"),
+        };
+        boot_dir.join(name).exists()
+    }
+}
+
+impl InitBackend for Dinit {
+    fn status(&self, unit: &str) -> Result<UnitStatus, ServiceError> {
+        let stdout = match self.run("status", unit) {
+            Ok(stdout) => stdout,
+            // unknown services fail to load
+            Err(ServiceError::CommandFailed(_)) => {
+                return Ok(UnitStatus {
+                    load_state: "not-found".into(),
+                    active_state: "inactive".into(),
+                    sub_state: "dead".into(),
+                    ..Default::default()
+                });
+            }
+            Err(e) => return Err(e),
+        };
+
+        let property = |name: &str| {
+            stdout
+                .lines()
+                .find_map(|line| line.trim().strip_prefix(name)?.strip_prefix(':'))
+                .unwrap_or_default()
+                .trim()
+                .to_string()
+        };
+        // e.g. "STARTED" or "STOPPED (failed to start; ...)"
+        let state = property("State");
+        let active_state = match state.split_whitespace().next().unwrap_or_default() {
+            "STARTED" => "active",
+            "STARTING" => "activating",
+            "STOPPING" => "deactivating",
+            _ if state.contains("failed") => "failed",
+            _ => "inactive",
+        };
+        let unit_file_state = match self.is_enabled(service_name(unit)) {
+            true => "enabled",
+            false => "disabled",
+        };
+        Ok(UnitStatus {
+            load_state: "loaded".into(),
+            active_state: active_state.into(),
+            sub_state: state.to_lowercase(),
+            unit_file_state: unit_file_state.into(),
+            main_pid: property("Process ID").parse().unwrap_or_default(),
+            ..Default::default()
+        })
+    }
+
+    fn start(&self, unit: &str) -> Result<(), ServiceError> {
+        self.run("start", unit).map(|_| ())
+    }
+
+    fn stop(&self, unit: &str) -> Result<(), ServiceError> {
+        self.run("stop", unit).map(|_| ())
+    }
+
+    fn restart(&self, unit: &str) -> Result<(), ServiceError> {
+        self.run("restart", unit).map(|_| ())
+    }
+
+    fn set_enabled(&self, unit: &str, enabled: bool) -> Result<(), ServiceError> {
+        let command = if enabled { "enable" } else { "disable" };
+        self.run(command, unit).map(|_| ())
+    }
+
+    fn set_masked(&self, _unit: &str, _masked: bool) -> Result<(), ServiceError> {
+        Err(ServiceError::Unsupported(InitSystem::Dinit.name()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::process::fake;
+
+    #[test]
+    fn systemctl_status() {
+        let show = include_str!("../tests/fixtures/systemd/show-256-running.txt");
+        fake::with_answers(&[("systemctl show", true, show)], || {
+            let backend = Systemctl {
+                scope: Scope::System,
+                escalate: false,
+            };
+            let status = backend.status("tailscaled.service").unwrap();
+
+            assert!(status.is_active());
+            assert!(status.is_enabled());
+            assert!(!status.is_failed());
+            assert_eq!(status.sub_state, "running");
+            assert_eq!(status.main_pid, 812);
+            assert_eq!(status.memory_current, Some(47448064));
+            assert_eq!(status.memory_peak, None);
+            assert_eq!(
+                status.cpu_usage,
+                Some(Duration::from_secs(183) + Duration::from_millis(412))
+            );
+            assert_eq!(status.active_since, DateTime::from_timestamp(1760425200, 0));
+            assert_eq!(status.restarts, 2);
+            assert_eq!(status.next_elapse, None);
+        });
+    }
+
+    #[test]
+    fn systemctl_status_before_248() {
+        // no `--timestamp=unix`, MemoryPeak or "[not set]" on systemd 239
+        let unrecognized = "systemctl: unrecognized option '--timestamp=unix'\n";
+        let backend = Systemctl {
+            scope: Scope::System,
+            escalate: false,
+        };
+
+        let running = include_str!("../tests/fixtures/systemd/show-239-running.txt");
+        fake::with_answers(
+            &[
+                ("systemctl show --timestamp=unix", false, unrecognized),
+                ("systemctl show", true, running),
+            ],
+            || {
+                let status = backend.status("tailscaled.service").unwrap();
+                assert!(status.is_active());
+                assert!(status.is_enabled());
+                assert_eq!(status.main_pid, 812);
+                assert_eq!(status.memory_current, None);
+                assert_eq!(status.memory_peak, None);
+                assert_eq!(status.cpu_usage, None);
+                assert_eq!(
+                    status.active_since,
+                    DateTime::parse_from_rfc3339("2026-10-13T08:00:00Z")
+                        .ok()
+                        .map(|time| time.with_timezone(&Utc))
+                );
+            },
+        );
+
+        // restarting after a crash, the main process is gone until then
+        let restarting = include_str!("../tests/fixtures/systemd/show-239-auto-restart.txt");
+        fake::with_answers(
+            &[
+                ("systemctl show --timestamp=unix", false, unrecognized),
+                ("systemctl show", true, restarting),
+            ],
+            || {
+                let status = backend.status("tailscaled.service").unwrap();
+                assert!(!status.is_active());
+                assert!(!status.is_failed());
+                assert_eq!(status.sub_state, "auto-restart");
+                assert_eq!(status.restarts, 3);
+                assert_eq!(status.active_since, None);
+            },
+        );
+    }
+
+    #[test]
+    fn dependency_tree() {
+        let plain = "tailscaled.service\n  -.mount\n  system.slice\n    -.slice\n  sysinit.target\n    dev-hugepages.mount\n";
+        let tree = "tailscaled.service\n\
+                    ● ├─-.mount\n\
+                    ● ├─system.slice\n\
+                    ● │ └─-.slice\n\
+                    ○ └─sysinit.target\n\
+                    ●   └─dev-hugepages.mount\n";
+        let expected = [
+            (0, "-.mount"),
+            (0, "system.slice"),
+            (1, "-.slice"),
+            (0, "sysinit.target"),
+            (1, "dev-hugepages.mount"),
+        ]
+        .map(|(depth, unit)| (depth, unit.to_string()));
+
+        assert_eq!(parse_dependencies(plain), expected);
+        assert_eq!(parse_dependencies(tree), expected);
+        assert!(parse_dependencies("tailscaled.service\n").is_empty());
+    }
+
+    #[test]
+    fn systemctl_failure() {
+        let stderr = "Failed to start tailscaled.service: Access denied\n";
+        fake::with_answers(&[("systemctl --user start", false, stderr)], || {
+            let backend = Systemctl {
+                scope: Scope::User,
+                escalate: false,
+            };
+            match backend.start("tailscaled.service") {
+                Err(ServiceError::CommandFailed(message)) => assert_eq!(message, stderr),
+                result => panic!("unexpected {result:?}"),
+            }
+        });
+    }
+
+    #[test]
+    fn openrc_translated_status() {
+        // rc-service runs in the C locale, should a translated status get
+        // through anyway the service reads as stopped instead of misread
+        let status = " * Status: gestartet\n";
+        fake::with_answers(&[("rc-service", true, status)], || {
+            let backend = OpenRc {
+                scope: Scope::System,
+            };
+            let status = backend.status("sshd.service").unwrap();
+            assert!(!status.is_active());
+        });
+    }
+}
@@ -0,0 +1,93 @@
+//! Mounting the home directory of a peer with sshfs, at `~/mnt/<hostname>`.
+//! Active mounts are read back from `/proc/mounts`, so mounts made outside of
+//! tailslint at the same place show up too.
+
+use std::io;
+use std::path::PathBuf;
+use std::process::Command;
+
+use crate::process;
+
+/// Whether `sshfs` is in PATH.
+pub fn is_installed() -> bool {
+    std::env::var_os("PATH")
+        .is_some_and(|path| std::env::split_paths(&path).any(|dir| dir.join("sshfs").is_file()))
+}
+
+/// The directory holding a mount point per peer, `~/mnt`.
+fn mount_dir() -> PathBuf {
+    let home = std::env::var_os("HOME").map(PathBuf::from);
+    home.unwrap_or_default().join("mnt")
+}
+
+/// Where the home directory of `hostname` is mounted.
+pub fn mount_point(hostname: &str) -> PathBuf {
+    mount_dir().join(hostname)
+}
+
+/// Mounts the home directory of the peer at `address` on `mount_point(hostname)`
+/// by running `sshfs [user@]address: <mount point>`. Without a user, ssh picks
+/// one as usual, from `~/.ssh/config` or the local user name.
+pub fn mount(hostname: &str, address: &str, user: Option<&str>) -> io::Result<PathBuf> {
+    let mount_point = mount_point(hostname);
+    std::fs::create_dir_all(&mount_point)?;
+
+    let remote = match user {
+        Some(user) => format!("{user}@{address}:"),
+        None => format!("{address}:"),
+    };
+    let result = run(Command::new("sshfs")
+        .arg(remote)
+        .arg(&mount_point)
+        .args(["-o", "reconnect,ServerAliveInterval=15"]));
+
+    if let Err(e) = result {
+        // only removes the directory if nothing was mounted on it
+        let _ = std::fs::remove_dir(&mount_point);
+        return Err(e);
+    }
+    Ok(mount_point)
+}
+
+/// Unmounts `mount_point(hostname)` by running `fusermount -u`.
+pub fn unmount(hostname: &str) -> io::Result<()> {
+    let mount_point = mount_point(hostname);
+    run(Command::new("fusermount").arg("-u").arg(&mount_point))?;
+    let _ = std::fs::remove_dir(&mount_point);
+    Ok(())
+}
+
+/// The host names with an sshfs mount at their mount point.
+pub fn mounted() -> Vec<String> {
+    let Ok(mounts) = std::fs::read_to_string("/proc/mounts") else {
+        return Vec::new();
+    };
+    let dir = mount_dir();
+
+    mounts
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let (_source, target, kind) = (fields.next()?, fields.next()?, fields.next()?);
+            if kind != "fuse.sshfs" {
+                return None;
+            }
+            // spaces in mount points are escaped as \040
+            let target = PathBuf::from(target.replace("\\040", " "));
+            match target.parent() == Some(dir.as_path()) {
+                true => Some(target.file_name()?.to_string_lossy().to_string()),
+                false => None,
+            }
+        })
+        .collect()
+}
+
+fn run(command: &mut Command) -> io::Result<()> {
+    let output = process::output(command)?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        return Err(io::Error::other(stderr));
+    }
+    Ok(())
+}
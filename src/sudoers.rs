@@ -0,0 +1,229 @@
+//! The `install-sudoers` and `install-doas-rule` subcommands and their
+//! `uninstall-` counterparts, for users whose `escalation` is sudo or doas and
+//! who want to change units without a password. The rules only cover the
+//! exact commands tailslint runs to start, stop, restart, enable, disable and
+//! mask the listed units, see `service::privileged_commands()`, for the user
+//! who ran the subcommand through sudo or doas, and are checked with `visudo
+//! -c` and `doas -C` before they take effect. All of them have to run as root.
+
+use std::io;
+use std::path::Path;
+
+use crate::{process, service};
+
+/// The drop-in sudo reads from its `@includedir`.
+const SUDOERS_PATH: &str = "/etc/sudoers.d/tailslint";
+
+/// doas reads a single file, the rules go between these lines.
+const DOAS_PATH: &str = "/etc/doas.conf";
+const DOAS_BEGIN: &str = "# BEGIN tailslint, written by `tailslint install-doas-rule`";
+const DOAS_END: &str = "# END tailslint";
+
+/// The user the rules are for, the one who invoked sudo or doas.
+fn user() -> io::Result<String> {
+    std::env::var("SUDO_USER")
+        .or_else(|_| std::env::var("DOAS_USER"))
+        .map_err(|_| io::Error::other("run this through sudo or doas"))
+}
+
+/// `units`, tailscaled's unit if none are given. Only names that need no
+/// quoting in either file are accepted, which covers every real unit name.
+fn units(units: &[String]) -> io::Result<Vec<String>> {
+    let units = match units.is_empty() {
+        true => vec!["tailscaled.service".to_string()],
+        false => units.to_vec(),
+    };
+    let plain = |c: char| c.is_ascii_alphanumeric() || "@._-".contains(c);
+    if let Some(unit) = units.iter().find(|unit| !unit.chars().all(plain)) {
+        return Err(io::Error::other(format!("{unit} is not a unit name")));
+    }
+    Ok(units)
+}
+
+/// The commands to allow, with the program resolved to its absolute path. sudo
+/// resolves the program it is asked to run itself, doas compares it as given,
+/// which is why `service::escalated_command()` runs programs by path as well.
+fn commands(units: &[String]) -> io::Result<Vec<Vec<String>>> {
+    let mut commands = Vec::new();
+    for unit in units {
+        for (program, args) in service::privileged_commands(unit) {
+            commands.push(command(program, &args)?);
+        }
+    }
+    Ok(commands)
+}
+
+fn command(program: &str, args: &[String]) -> io::Result<Vec<String>> {
+    let path = service::which(program)
+        .ok_or_else(|| io::Error::other(format!("{program} is not in PATH")))?;
+    let mut command = vec![path.to_string_lossy().to_string()];
+    command.extend(args.iter().cloned());
+    Ok(command)
+}
+
+/// The drop-in allowing `user` to run `commands` without a password.
+fn sudoers(user: &str, commands: &[Vec<String>]) -> String {
+    let commands: Vec<String> = commands.iter().map(|command| command.join(" ")).collect();
+    format!(
+        "# Written by `tailslint install-sudoers`, lets {user} start and stop\n\
+         # the units below without a password.\n\
+         {user} ALL=(root) NOPASSWD: {}\n",
+        commands.join(", \\\n    "),
+    )
+}
+
+/// The doas.conf block allowing `user` to run `commands` without a password.
+fn doas_rules(user: &str, commands: &[Vec<String>]) -> String {
+    let mut block = format!("{DOAS_BEGIN}\n");
+    for command in commands {
+        let (program, args) = command.split_first().expect("commands have a program");
+        block.push_str(&format!(
+            "permit nopass {user} as root cmd {program} args {}\n",
+            args.join(" ")
+        ));
+    }
+    block.push_str(DOAS_END);
+    block.push('\n');
+    block
+}
+
+/// `config` without a block written by `install-doas-rule`.
+fn without_doas_rules(config: &str) -> String {
+    let mut kept = String::new();
+    let mut inside = false;
+    for line in config.lines() {
+        match line {
+            DOAS_BEGIN => inside = true,
+            DOAS_END if inside => inside = false,
+            _ if !inside => {
+                kept.push_str(line);
+                kept.push('\n');
+            }
+            _ => {}
+        }
+    }
+    kept
+}
+
+/// Runs a config check like `visudo -c -f <file>`, failing with its output.
+fn check(program: &str, args: &[&str]) -> io::Result<()> {
+    let output = process::run(program, args)?;
+    if !output.success {
+        let message = format!("{}{}", output.stdout, output.stderr);
+        return Err(io::Error::other(message.trim().to_string()));
+    }
+    Ok(())
+}
+
+/// Writes `contents` next to `path`, checks the copy with `check` and only then
+/// moves it into place, so a broken rule never locks anyone out.
+fn replace(
+    path: &Path,
+    contents: &str,
+    mode: u32,
+    check: impl FnOnce(&Path) -> io::Result<()>,
+) -> io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    // sudo skips files in sudoers.d with a dot in their name
+    let staged = path.with_file_name(format!(
+        ".{}.new",
+        path.file_name().unwrap_or_default().to_string_lossy()
+    ));
+    std::fs::write(&staged, contents)?;
+    std::fs::set_permissions(&staged, std::fs::Permissions::from_mode(mode))?;
+
+    if let Err(e) = check(&staged) {
+        let _ = std::fs::remove_file(&staged);
+        return Err(e);
+    }
+    std::fs::rename(&staged, path)
+}
+
+/// Writes the sudoers drop-in for `units`, tailscaled's unit if none are given.
+pub fn install_sudoers(units: &[String]) -> io::Result<&'static Path> {
+    let rules = sudoers(&user()?, &commands(&self::units(units)?)?);
+    let path = Path::new(SUDOERS_PATH);
+    replace(path, &rules, 0o440, |staged| {
+        check("visudo", &["-c", "-q", "-f", &staged.to_string_lossy()])
+    })?;
+
+    // the drop-in is fine on its own, make sure it also is with the rest
+    if let Err(e) = check("visudo", &["-c", "-q"]) {
+        let _ = std::fs::remove_file(path);
+        return Err(e);
+    }
+    Ok(path)
+}
+
+/// Removes the sudoers drop-in, if it was installed.
+pub fn uninstall_sudoers() -> io::Result<()> {
+    match std::fs::remove_file(SUDOERS_PATH) {
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+        result => result,
+    }
+}
+
+/// Adds the rules for `units` to doas.conf, replacing earlier ones.
+pub fn install_doas_rule(units: &[String]) -> io::Result<&'static Path> {
+    let rules = doas_rules(&user()?, &commands(&self::units(units)?)?);
+    let path = Path::new(DOAS_PATH);
+    let config = match std::fs::read_to_string(path) {
+        Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+        config => config?,
+    };
+
+    let contents = without_doas_rules(&config) + &rules;
+    replace(path, &contents, 0o600, |staged| {
+        check("doas", &["-C", &staged.to_string_lossy()])
+    })?;
+    Ok(path)
+}
+
+/// Removes the rules added by `install_doas_rule()` from doas.conf.
+pub fn uninstall_doas_rule() -> io::Result<()> {
+    let path = Path::new(DOAS_PATH);
+    let config = match std::fs::read_to_string(path) {
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+        config => config?,
+    };
+    if !config.lines().any(|line| line == DOAS_BEGIN) {
+        return Ok(());
+    }
+
+    replace(path, &without_doas_rules(&config), 0o600, |staged| {
+        check("doas", &["-C", &staged.to_string_lossy()])
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::service::Escalation;
+
+    #[test]
+    fn doas_rules_match_the_commands_run() {
+        for (program, args) in service::privileged_commands("tailscaled.service") {
+            // there is no rule for programs that aren't installed
+            let Ok(command) = command(program, &args) else {
+                continue;
+            };
+            let rules = doas_rules("alice", &[command]);
+
+            // doas -n -- <program> <args>
+            let args: Vec<&str> = args.iter().map(String::as_str).collect();
+            let (escalation, run) =
+                service::escalated_command(Escalation::Doas, program, &args).unwrap();
+            let run = &run[run.iter().position(|arg| arg == "--").unwrap() + 1..];
+            assert_eq!(escalation, "doas");
+            assert_eq!(
+                rules.lines().nth(1).unwrap(),
+                format!(
+                    "permit nopass alice as root cmd {} args {}",
+                    run[0],
+                    run[1..].join(" ")
+                )
+            );
+        }
+    }
+}
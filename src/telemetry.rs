@@ -0,0 +1,71 @@
+//! Opt-in counters of which features get used, to guide maintenance priorities.
+//!
+//! Only feature names and counts are recorded, never identifiers, hostnames or
+//! addresses, and nothing is ever sent anywhere: the counts are kept in
+//! `telemetry.json` in the config directory where they can be inspected.
+//! Without the `telemetry` cargo feature every function is a no-op.
+
+#[cfg(feature = "telemetry")]
+mod enabled {
+    use std::collections::BTreeMap;
+    use std::path::PathBuf;
+    use std::sync::Mutex;
+
+    use crate::config;
+
+    /// The counts, `None` until recording was switched on by `init()`.
+    static COUNTS: Mutex<Option<BTreeMap<String, u64>>> = Mutex::new(None);
+
+    fn path() -> Option<PathBuf> {
+        config::config_dir().map(|dir| dir.join("telemetry.json"))
+    }
+
+    /// Starts recording if the user opted in, continuing from the stored counts.
+    pub fn init(opted_in: bool) {
+        if !opted_in {
+            return;
+        }
+
+        let counts = path()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default();
+        *COUNTS.lock().unwrap() = Some(counts);
+    }
+
+    /// Counts one use of `feature`.
+    pub fn record(feature: &str) {
+        let mut counts = COUNTS.lock().unwrap();
+        let Some(counts) = counts.as_mut() else {
+            return;
+        };
+
+        *counts.entry(feature.to_string()).or_default() += 1;
+
+        if let (Some(path), Ok(json)) = (path(), serde_json::to_string_pretty(&*counts)) {
+            let _ = std::fs::write(path, json);
+        }
+    }
+
+    /// Everything recorded so far, or `None` if recording is off.
+    pub fn report() -> Option<Vec<(String, u64)>> {
+        let counts = COUNTS.lock().unwrap();
+        counts
+            .as_ref()
+            .map(|counts| counts.iter().map(|(k, v)| (k.clone(), *v)).collect())
+    }
+}
+
+#[cfg(feature = "telemetry")]
+pub use enabled::{init, record, report};
+
+#[cfg(not(feature = "telemetry"))]
+pub fn init(_opted_in: bool) {}
+
+#[cfg(not(feature = "telemetry"))]
+pub fn record(_feature: &str) {}
+
+#[cfg(not(feature = "telemetry"))]
+pub fn report() -> Option<Vec<(String, u64)>> {
+    None
+}
@@ -0,0 +1,77 @@
+//! The onboarding tour shown on first run and from Help > "Take the tour".
+//!
+//! Each step names the part of the window it explains; the window outlines that
+//! part while the step is shown. Finishing or skipping the tour leaves a marker
+//! in the config directory so it is not shown again on the next start.
+
+use std::path::PathBuf;
+
+use crate::TourStepData;
+use crate::config;
+
+/// The steps in the order they are shown, as (target, title, text). The target
+/// is one of "toggle", "machines" or "" for steps without an outlined area.
+const STEPS: [(&str, &str, &str); 6] = [
+    (
+        "",
+        "Welcome to Tailslint",
+        "A quick look at what the window can do. Use Next to continue or Skip to close the tour.",
+    ),
+    (
+        "toggle",
+        "Connect and disconnect",
+        "The switch brings tailscale up or down. Space does the same from the keyboard.",
+    ),
+    (
+        "machines",
+        "Your machines",
+        "Every machine in the tailnet, online ones marked green. Click one, or select it with ↑/↓ and press Enter, to copy its IP.",
+    ),
+    (
+        "",
+        "Tailscale menu",
+        "Copy this machine's addresses, switch accounts, run netcheck, pick an exit node or export the machine list.",
+    ),
+    (
+        "",
+        "Serve and Taildrive",
+        "Share a local port or a folder with the tailnet, and turn sharing off again, from their menus.",
+    ),
+    (
+        "",
+        "Getting help",
+        "F1 lists the keyboard shortcuts. The Help menu shows the installed version and can restart this tour.",
+    ),
+];
+
+fn marker_path() -> Option<PathBuf> {
+    config::config_dir().map(|dir| dir.join("tour-done"))
+}
+
+/// The tour, ready to be handed to the window.
+pub fn steps() -> Vec<TourStepData> {
+    STEPS
+        .iter()
+        .map(|(target, title, text)| TourStepData {
+            target: (*target).into(),
+            title: (*title).into(),
+            text: (*text).into(),
+        })
+        .collect()
+}
+
+/// Whether the tour was never finished or skipped before.
+pub fn is_first_run() -> bool {
+    marker_path().is_some_and(|path| !path.exists())
+}
+
+/// Remembers that the tour was seen.
+pub fn mark_done() {
+    let Some(path) = marker_path() else {
+        return;
+    };
+    if let Some(dir) = path.parent() {
+        let _ = std::fs::create_dir_all(dir);
+    }
+    let _ = std::fs::write(path, "");
+}
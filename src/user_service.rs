@@ -0,0 +1,107 @@
+//! The `install-service` and `uninstall-service` subcommands, which run
+//! tailslint as a systemd user service instead of through XDG autostart.
+//! systemd restarts it should it crash, and it is started with the graphical
+//! session, once the session exported its display variables to systemd, and
+//! restarted by the watchdog should the window stop responding (see `sd_notify.rs`).
+//! The watchdog timeout is derived from `command_timeout_secs`.
+
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::Duration;
+
+use crate::{config, process, sd_notify};
+
+/// The unit's file name in `~/.config/systemd/user`.
+const UNIT: &str = "tailslint.service";
+
+/// Variables the window needs to reach the display, imported into the user
+/// manager on install so the service can start right away.
+const DISPLAY_VARIABLES: [&str; 3] = ["WAYLAND_DISPLAY", "DISPLAY", "XAUTHORITY"];
+
+fn unit_path() -> Option<PathBuf> {
+    config::xdg_config_home().map(|dir| dir.join("systemd/user").join(UNIT))
+}
+
+/// The unit running `exe`, watched by a watchdog with `watchdog` as timeout if
+/// there is one. `%` and `$` would be expanded by systemd and so are escaped
+/// along with the quoting characters.
+fn unit_file(exe: &Path, watchdog: Option<Duration>) -> String {
+    let exe = exe.to_string_lossy();
+    let mut quoted = String::new();
+    for c in exe.chars() {
+        match c {
+            '"' | '\\' => quoted.extend(['\\', c]),
+            '%' => quoted.push_str("%%"),
+            '$' => quoted.push_str("$$"),
+            c => quoted.push(c),
+        }
+    }
+    let watchdog = watchdog
+        .map(|timeout| format!("WatchdogSec={}\n", timeout.as_secs()))
+        .unwrap_or_default();
+
+    format!(
+        "[Unit]\n\
+         Description=Tailslint, a Tailscale control window\n\
+         PartOf=graphical-session.target\n\
+         After=graphical-session.target\n\
+         \n\
+         [Service]\n\
+         Type=notify\n\
+         {watchdog}\
+         ExecStart=\"{quoted}\"\n\
+         Restart=on-failure\n\
+         RestartSec=5\n\
+         \n\
+         [Install]\n\
+         WantedBy=graphical-session.target\n"
+    )
+}
+
+/// Writes the unit for the running executable and enables and starts it.
+pub fn install(command_timeout: Duration) -> io::Result<PathBuf> {
+    let path = unit_path().ok_or_else(|| io::Error::other("no config directory"))?;
+    let exe = std::env::current_exe()?;
+
+    std::fs::create_dir_all(path.parent().unwrap_or(&path))?;
+    let watchdog = sd_notify::watchdog_timeout(command_timeout);
+    std::fs::write(&path, unit_file(&exe, watchdog))?;
+
+    let present: Vec<&str> = DISPLAY_VARIABLES
+        .into_iter()
+        .filter(|var| std::env::var_os(var).is_some())
+        .collect();
+    if !present.is_empty() {
+        let mut args = vec!["import-environment"];
+        args.extend(present);
+        systemctl(&args)?;
+    }
+
+    systemctl(&["daemon-reload"])?;
+    systemctl(&["enable", "--now", UNIT])?;
+    Ok(path)
+}
+
+/// Stops and disables the unit and removes its file.
+pub fn uninstall() -> io::Result<()> {
+    let path = unit_path().ok_or_else(|| io::Error::other("no config directory"))?;
+    if !path.exists() {
+        return Ok(());
+    }
+
+    systemctl(&["disable", "--now", UNIT])?;
+    std::fs::remove_file(&path)?;
+    systemctl(&["daemon-reload"])
+}
+
+/// Runs `systemctl --user <args>`.
+fn systemctl(args: &[&str]) -> io::Result<()> {
+    let output = process::output(Command::new("systemctl").arg("--user").args(args))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        return Err(io::Error::other(stderr));
+    }
+    Ok(())
+}
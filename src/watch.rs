@@ -0,0 +1,41 @@
+//! Live updates from tailscaled's IPN notification bus.
+//!
+//! A background thread stays subscribed to `watch-ipn-bus` on the LocalAPI and
+//! refreshes the window whenever tailscaled announces a change, reconnecting
+//! after tailscaled restarts. Without access to the LocalAPI the window is only
+//! refreshed after its own actions.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::Duration;
+
+use crate::{MainWindow, demo, localapi, update_tailscale_state};
+
+/// How long to wait before subscribing again after the stream ended.
+const RETRY: Duration = Duration::from_secs(5);
+
+/// Starts refreshing `main_window` on every change tailscaled announces.
+pub fn start(main_window_weak: slint::Weak<MainWindow>) {
+    if demo::is_active() {
+        return;
+    }
+
+    let pending = Arc::new(AtomicBool::new(false));
+    thread::spawn(move || {
+        loop {
+            let _ = localapi::watch("/localapi/v0/watch-ipn-bus?mask=0", |_| {
+                // a burst of notifications only needs one refresh
+                if pending.swap(true, Ordering::AcqRel) {
+                    return;
+                }
+                let pending = pending.clone();
+                let _ = main_window_weak.upgrade_in_event_loop(move |main_window| {
+                    pending.store(false, Ordering::Release);
+                    update_tailscale_state(&main_window);
+                });
+            });
+            thread::sleep(RETRY);
+        }
+    });
+}